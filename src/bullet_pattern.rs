@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use nanoserde::DeJson;
+
+use crate::entity::projectile::{Homing, ProjectileModifiers, SplitOnImpact};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ShotKind {
+    Ring,
+    Spread,
+    Aimed,
+}
+
+impl ShotKind {
+    pub fn from_name(name: &str) -> Option<ShotKind> {
+        match name {
+            "ring" => Some(ShotKind::Ring),
+            "spread" => Some(ShotKind::Spread),
+            "aimed" => Some(ShotKind::Aimed),
+            _ => None,
+        }
+    }
+}
+
+/// A single volley. Angles are in degrees, `ring` fires `count` bullets evenly around
+/// the emitter, `spread` fans them over `arc` around `angle` and `aimed` does the same
+/// around the direction to the player. A volley is fired `repeat` times, `interval`
+/// seconds apart, rotating by `spin` each time, after waiting `delay` seconds.
+/// `pierce`, `bounces`, `homing` (degrees per second), `split` and `range` add
+/// projectile modifiers to every bullet of the step.
+#[derive(DeJson, Clone, Debug)]
+pub struct PatternStep {
+    pub kind: String,
+    pub count: usize,
+    pub speed: f32,
+    #[nserde(default)]
+    pub end_speed: Option<f32>,
+    #[nserde(default)]
+    pub speed_time: f32,
+    #[nserde(default)]
+    pub angle: f32,
+    #[nserde(default)]
+    pub arc: f32,
+    #[nserde(default)]
+    pub spin: f32,
+    #[nserde(default)]
+    pub repeat: usize,
+    #[nserde(default)]
+    pub interval: f32,
+    #[nserde(default)]
+    pub delay: f32,
+    #[nserde(default)]
+    pub sound_volume: f32,
+    #[nserde(default)]
+    pub pierce: u32,
+    #[nserde(default)]
+    pub bounces: u32,
+    #[nserde(default)]
+    pub homing: f32,
+    #[nserde(default)]
+    pub split: usize,
+    #[nserde(default)]
+    pub range: Option<f32>,
+}
+
+impl PatternStep {
+    pub fn shot_kind(&self) -> ShotKind {
+        ShotKind::from_name(&self.kind).unwrap()
+    }
+
+    pub fn repeat_count(&self) -> usize {
+        self.repeat.max(1)
+    }
+
+    pub fn modifiers(&self) -> ProjectileModifiers {
+        ProjectileModifiers {
+            pierce: self.pierce,
+            bounces: self.bounces,
+            homing: (self.homing > 0.).then(|| Homing {
+                turn_speed: self.homing.to_radians(),
+                range: 200.,
+            }),
+            split: (self.split > 0).then(|| SplitOnImpact {
+                count: self.split,
+                speed: self.speed * 0.8,
+                damage_multiplier: 1.,
+            }),
+            range: self.range,
+        }
+    }
+}
+
+#[derive(DeJson, Clone, Debug)]
+pub struct BulletPattern {
+    pub name: String,
+    pub steps: Vec<PatternStep>,
+}
+
+#[derive(DeJson)]
+struct BulletPatternFile {
+    patterns: Vec<BulletPattern>,
+}
+
+pub struct BulletPatterns {
+    patterns: HashMap<String, BulletPattern>,
+}
+
+impl BulletPatterns {
+    pub fn from_json(json: &str) -> Self {
+        let file = BulletPatternFile::deserialize_json(json).unwrap();
+        let mut patterns = HashMap::new();
+        for pattern in file.patterns {
+            for step in &pattern.steps {
+                assert!(
+                    ShotKind::from_name(&step.kind).is_some(),
+                    "unknown shot kind '{}' in bullet pattern '{}'",
+                    step.kind,
+                    pattern.name
+                );
+                assert!(
+                    step.count > 0,
+                    "bullet pattern '{}' has a step without bullets",
+                    pattern.name
+                );
+            }
+            patterns.insert(pattern.name.clone(), pattern);
+        }
+        Self { patterns }
+    }
+
+    pub fn get(&self, name: &str) -> &BulletPattern {
+        self.patterns
+            .get(name)
+            .unwrap_or_else(|| panic!("missing bullet pattern '{}'", name))
+    }
+}
@@ -0,0 +1,263 @@
+use nanoserde::DeJson;
+
+use crate::{
+    rand_utils::SeededRng,
+    room::{Enemy, WaveConfig},
+};
+
+#[derive(DeJson, Clone)]
+pub struct BudgetCurve {
+    pub base: f32,
+    pub per_room: f32,
+    pub per_room_squared: f32,
+    pub max: f32,
+}
+
+impl BudgetCurve {
+    pub fn budget(&self, room_index: usize) -> f32 {
+        let room = room_index as f32;
+        (self.base + self.per_room * room + self.per_room_squared * room * room).min(self.max)
+    }
+}
+
+#[derive(DeJson, Clone)]
+pub struct WaveProfile {
+    pub wave_size: usize,
+    pub kills_to_advance: usize,
+    pub wave_time: f32,
+    pub spawn_warning_time: f32,
+}
+
+#[derive(DeJson, Clone)]
+pub struct EnemyProfile {
+    pub enemy: String,
+    pub cost: f32,
+    pub weight: f32,
+    pub min_count: usize,
+    pub max_count: usize,
+    pub unlock_room: usize,
+}
+
+#[derive(DeJson, Clone)]
+pub struct EliteProfile {
+    pub unlock_room: usize,
+    pub base_chance: f32,
+    pub chance_per_room: f32,
+    pub max_chance: f32,
+    /// Every this many rooms past the unlock, elites roll one more affix.
+    pub rooms_per_affix: usize,
+    pub max_affixes: usize,
+    pub hp_multiplier: f32,
+}
+
+impl EliteProfile {
+    pub fn chance(&self, room_index: usize) -> f32 {
+        if room_index < self.unlock_room {
+            return 0.;
+        }
+        let rooms = (room_index - self.unlock_room) as f32;
+        (self.base_chance + self.chance_per_room * rooms).min(self.max_chance)
+    }
+
+    pub fn affix_count(&self, room_index: usize) -> usize {
+        let rooms = room_index.saturating_sub(self.unlock_room);
+        (1 + rooms / self.rooms_per_affix.max(1)).min(self.max_affixes)
+    }
+}
+
+#[derive(DeJson, Clone)]
+pub struct DifficultyProfile {
+    pub budget: BudgetCurve,
+    pub waves: WaveProfile,
+    pub enemies: Vec<EnemyProfile>,
+    pub elites: EliteProfile,
+}
+
+impl DifficultyProfile {
+    pub fn from_json(json: &str) -> Self {
+        let profile = DifficultyProfile::deserialize_json(json).unwrap();
+        for enemy in &profile.enemies {
+            match Enemy::from_name(&enemy.enemy) {
+                None => panic!("unknown enemy '{}' in difficulty profile", enemy.enemy),
+                // The boss only appears in boss rooms, never in regular waves
+                Some(Enemy::Mirituhg) => panic!("boss '{}' in difficulty profile", enemy.enemy),
+                Some(_) => {}
+            }
+            assert!(enemy.cost > 0., "enemy cost must be positive");
+        }
+        profile
+    }
+
+    pub fn wave_config(&self) -> WaveConfig {
+        WaveConfig {
+            wave_size: self.waves.wave_size,
+            kills_to_advance: self.waves.kills_to_advance,
+            wave_time: self.waves.wave_time,
+            spawn_warning_time: self.waves.spawn_warning_time,
+        }
+    }
+
+    /// Composes the enemies for a room from its budget. Every pick spends budget or
+    /// exhausts an enemy's max count, so this always terminates.
+    pub fn compose_encounter(&self, room_index: usize, seed: u64) -> Vec<Enemy> {
+        let mut rng = SeededRng::new(seed ^ (room_index as u64).wrapping_mul(0x2545_F491));
+        let mut remaining_budget = self.budget.budget(room_index);
+
+        let available = self
+            .enemies
+            .iter()
+            .filter(|e| e.unlock_room <= room_index)
+            .collect::<Vec<_>>();
+        let mut counts = vec![0; available.len()];
+
+        for (i, profile) in available.iter().enumerate() {
+            while counts[i] < profile.min_count && profile.cost <= remaining_budget {
+                counts[i] += 1;
+                remaining_budget -= profile.cost;
+            }
+        }
+
+        loop {
+            let affordable = available
+                .iter()
+                .enumerate()
+                .filter(|(i, e)| e.cost <= remaining_budget && counts[*i] < e.max_count)
+                .collect::<Vec<_>>();
+            if affordable.len() == 0 {
+                break;
+            }
+
+            let total_weight: f32 = affordable.iter().map(|(_, e)| e.weight.max(0.)).sum();
+            let mut roll = rng.next_f32() * total_weight;
+            let mut chosen = affordable[affordable.len() - 1].0;
+            for (i, profile) in &affordable {
+                roll -= profile.weight.max(0.);
+                if roll <= 0. {
+                    chosen = *i;
+                    break;
+                }
+            }
+
+            counts[chosen] += 1;
+            remaining_budget -= available[chosen].cost;
+        }
+
+        let mut enemies = vec![];
+        for (i, profile) in available.iter().enumerate() {
+            let enemy = Enemy::from_name(&profile.enemy).unwrap();
+            for _ in 0..counts[i] {
+                enemies.push(enemy);
+            }
+        }
+        for i in (1..enemies.len()).rev() {
+            let j = rng.gen_range(0, i + 1);
+            enemies.swap(i, j);
+        }
+        enemies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile() -> DifficultyProfile {
+        DifficultyProfile::from_json(include_str!("../assets/data/difficulty.json"))
+    }
+
+    fn cost(profile: &DifficultyProfile, enemy: Enemy) -> f32 {
+        profile
+            .enemies
+            .iter()
+            .find(|e| Enemy::from_name(&e.enemy) == Some(enemy))
+            .unwrap()
+            .cost
+    }
+
+    #[test]
+    fn same_seed_composes_same_encounter() {
+        let profile = profile();
+        for room_index in 0..12 {
+            assert_eq!(
+                profile.compose_encounter(room_index, 42),
+                profile.compose_encounter(room_index, 42)
+            );
+        }
+    }
+
+    #[test]
+    fn encounter_stays_within_budget() {
+        let profile = profile();
+        for seed in 0..50 {
+            for room_index in 0..30 {
+                let spent: f32 = profile
+                    .compose_encounter(room_index, seed)
+                    .into_iter()
+                    .map(|enemy| cost(&profile, enemy))
+                    .sum();
+                assert!(spent <= profile.budget.budget(room_index));
+            }
+        }
+    }
+
+    #[test]
+    fn encounter_respects_unlocks_and_counts() {
+        let profile = profile();
+        for seed in 0..50 {
+            for room_index in 0..30 {
+                let encounter = profile.compose_encounter(room_index, seed);
+                for enemy_profile in &profile.enemies {
+                    let enemy = Enemy::from_name(&enemy_profile.enemy);
+                    let count = encounter.iter().filter(|e| Some(**e) == enemy).count();
+                    assert!(count <= enemy_profile.max_count);
+                    if room_index < enemy_profile.unlock_room {
+                        assert_eq!(count, 0);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn budget_curve_is_capped() {
+        let curve = BudgetCurve {
+            base: 2.,
+            per_room: 3.,
+            per_room_squared: 0.5,
+            max: 20.,
+        };
+        assert_eq!(curve.budget(0), 2.);
+        assert_eq!(curve.budget(2), 10.);
+        assert_eq!(curve.budget(100), 20.);
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown enemy")]
+    fn from_json_rejects_unknown_enemy() {
+        let json = include_str!("../assets/data/difficulty.json").replace("spitter", "slime");
+        DifficultyProfile::from_json(&json);
+    }
+
+    #[test]
+    #[should_panic(expected = "boss")]
+    fn from_json_rejects_boss() {
+        let json = include_str!("../assets/data/difficulty.json").replace("burrower", "mirituhg");
+        DifficultyProfile::from_json(&json);
+    }
+
+    #[test]
+    #[should_panic(expected = "cost must be positive")]
+    fn from_json_rejects_zero_cost() {
+        let json = include_str!("../assets/data/difficulty.json")
+            .replace("\"cost\": 2.0", "\"cost\": 0.0");
+        DifficultyProfile::from_json(&json);
+    }
+
+    #[test]
+    #[should_panic(expected = "cost must be positive")]
+    fn from_json_rejects_negative_cost() {
+        let json = include_str!("../assets/data/difficulty.json")
+            .replace("\"cost\": 5.0", "\"cost\": -5.0");
+        DifficultyProfile::from_json(&json);
+    }
+}
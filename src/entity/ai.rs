@@ -0,0 +1,221 @@
+use std::{collections::HashMap, rc::Rc};
+
+use macroquad::prelude::*;
+
+use crate::room::Enemy;
+
+pub enum Condition {
+    Always,
+    TimeInState(f32),
+    /// The state's random `duration` has run out.
+    StateTimeout,
+    AnimationFinished,
+    PlayerWithin(f32),
+    /// Fraction of the entity's max hp.
+    HpBelow(f32),
+    LineOfSight(bool),
+    NearAnchor(f32),
+    NearLockedTarget(f32),
+    /// The first state was entered more recently than the second one.
+    EnteredMoreRecently(&'static str, &'static str),
+    /// Seconds since the state was last entered, or since the machine started.
+    SinceEntered(&'static str, f32),
+    All(Vec<Condition>),
+}
+
+pub enum Steering {
+    /// Follows the flow field towards the player.
+    Seek,
+    Flee {
+        within: f32,
+    },
+    /// Seeks until `distance` from the player, slowing down over `slowing`.
+    Arrive {
+        distance: f32,
+        slowing: f32,
+    },
+    Separation {
+        radius: f32,
+    },
+    /// Circles the player at `radius`, in the machine's orbit direction.
+    Orbit {
+        radius: f32,
+    },
+}
+
+pub enum Movement {
+    Stand,
+    Navigate(f32),
+    KeepHeading(f32),
+    MoveToAnchor(f32),
+    MoveToLockedTarget(f32),
+    Wander {
+        speed: f32,
+        min_radius: f32,
+        max_radius: f32,
+        retarget_time: f32,
+    },
+    Steer {
+        speed: f32,
+        behaviors: Vec<(Steering, f32)>,
+    },
+}
+
+pub enum AiAction {
+    Emit {
+        pattern: &'static str,
+        offset: Vec2,
+        /// Radians per second the pattern is rotated by over the machine's lifetime.
+        spin: f32,
+    },
+    DamagePlayerWithin {
+        range: f32,
+        damage: f32,
+        knockback: f32,
+    },
+    SpawnEnemy(Enemy, Vec2),
+    Shake(f32, f32),
+    LockTarget,
+}
+
+pub struct AiEvent {
+    pub at: f32,
+    pub every: Option<f32>,
+    pub action: AiAction,
+}
+
+pub struct Transition {
+    pub condition: Condition,
+    /// One of these is picked at random when the condition holds.
+    pub targets: Vec<&'static str>,
+}
+
+pub struct AiState {
+    pub name: &'static str,
+    pub animation: Option<&'static str>,
+    pub movement: Movement,
+    pub duration: Option<(f32, f32)>,
+    pub on_enter: Vec<AiAction>,
+    pub events: Vec<AiEvent>,
+    pub on_exit: Vec<AiAction>,
+    pub transitions: Vec<Transition>,
+}
+
+impl AiState {
+    pub fn new(name: &'static str, animation: Option<&'static str>, movement: Movement) -> Self {
+        Self {
+            name,
+            animation,
+            movement,
+            duration: None,
+            on_enter: vec![],
+            events: vec![],
+            on_exit: vec![],
+            transitions: vec![],
+        }
+    }
+
+    pub fn duration(mut self, min: f32, max: f32) -> Self {
+        self.duration = Some((min, max));
+        self
+    }
+
+    pub fn on_enter(mut self, action: AiAction) -> Self {
+        self.on_enter.push(action);
+        self
+    }
+
+    pub fn at(mut self, time: f32, action: AiAction) -> Self {
+        self.events.push(AiEvent {
+            at: time,
+            every: None,
+            action,
+        });
+        self
+    }
+
+    pub fn every(mut self, interval: f32, action: AiAction) -> Self {
+        self.events.push(AiEvent {
+            at: 0.,
+            every: Some(interval),
+            action,
+        });
+        self
+    }
+
+    pub fn on_exit(mut self, action: AiAction) -> Self {
+        self.on_exit.push(action);
+        self
+    }
+
+    pub fn to(mut self, condition: Condition, target: &'static str) -> Self {
+        self.transitions.push(Transition {
+            condition,
+            targets: vec![target],
+        });
+        self
+    }
+
+    pub fn to_any(mut self, condition: Condition, targets: Vec<&'static str>) -> Self {
+        self.transitions.push(Transition { condition, targets });
+        self
+    }
+}
+
+pub struct AiBehavior {
+    pub states: Vec<AiState>,
+}
+
+impl AiBehavior {
+    pub fn new(states: Vec<AiState>) -> Self {
+        Self { states }
+    }
+
+    pub fn state_index(&self, name: &str) -> usize {
+        self.states
+            .iter()
+            .position(|state| state.name == name)
+            .unwrap_or_else(|| panic!("unknown ai state '{}'", name))
+    }
+}
+
+pub struct AiMachine {
+    pub behavior: Rc<AiBehavior>,
+    pub current: usize,
+    /// Set when the machine still has to run the current state's enter actions.
+    pub entering: bool,
+    pub time_in_state: f32,
+    pub state_duration: f32,
+    pub elapsed: f32,
+    pub entered_at: HashMap<&'static str, f32>,
+    pub max_hp: Option<f32>,
+    pub anchor: Vec2,
+    pub locked_target: Vec2,
+    pub wander_target: Option<Vec2>,
+    pub wander_time: f32,
+    pub orbit_sign: f32,
+}
+
+impl AiMachine {
+    pub fn new(behavior: AiBehavior, anchor: Vec2) -> Self {
+        Self {
+            behavior: Rc::new(behavior),
+            current: 0,
+            entering: true,
+            time_in_state: 0.,
+            state_duration: 0.,
+            elapsed: 0.,
+            entered_at: HashMap::new(),
+            max_hp: None,
+            anchor,
+            locked_target: anchor,
+            wander_target: None,
+            wander_time: 0.,
+            orbit_sign: if rand::gen_range(0, 2) == 0 { 1. } else { -1. },
+        }
+    }
+
+    pub fn state(&self) -> &AiState {
+        &self.behavior.states[self.current]
+    }
+}
@@ -32,6 +32,7 @@ pub struct AnimatedSprite {
     pub animations: HashMap<String, Animation>,
     pub current_animation: String,
     pub color: Color,
+    pub scale: f32,
 }
 
 impl AnimatedSprite {
@@ -42,6 +43,7 @@ impl AnimatedSprite {
             current_animation: animations.iter().next().unwrap().0.clone(),
             animations,
             color: WHITE,
+            scale: 1.,
         }
     }
 
@@ -79,7 +81,12 @@ impl AnimatedSprite {
     pub fn draw(&self, data: &GameData, position: Vec2, flipped: bool) {
         let (_, anim) = self.current_animation();
         let index = anim.frames[anim.current_frame];
-        self.indexed_sprite.draw(data, position, index, flipped)
+        if self.scale != 1. {
+            self.indexed_sprite
+                .draw_scaled(data, position, index, self.scale, flipped)
+        } else {
+            self.indexed_sprite.draw(data, position, index, flipped)
+        }
     }
 
     pub fn set_animation(&mut self, name: &str) {
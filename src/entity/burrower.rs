@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+use crate::{
+    game_data::GameData,
+    sprite::{flash_material::create_sprite_color_material, indexed_sprite::IndexedSprite},
+    systems::collision::{CircleCollider, ColliderType},
+    timer::Timer,
+};
+
+use super::{
+    ai::{AiAction, AiBehavior, AiMachine, AiState, Condition, Movement},
+    animated_sprite::{AnimatedSprite, Animation},
+    damage::{DamageType, Resistances},
+    entities::Ecs,
+    entity_id::Entity,
+    tags::{DamageOnCollision, Damageable, EntityType, Health},
+};
+
+/// AI state in which the burrower travels underground.
+pub const BURROWER_TUNNEL_STATE: &str = "tunnel";
+
+pub struct Burrower {
+    pub tunnel_speed: f32,
+    pub max_tunnel_time: f32,
+    pub erupt_range: f32,
+    pub erupt_damage_range: f32,
+    pub surface_time: f32,
+    pub submerged: bool,
+    pub dust_timer: Timer,
+}
+
+impl Burrower {
+    pub fn behavior(&self) -> AiBehavior {
+        AiBehavior::new(vec![
+            AiState::new("surface", Some("idle"), Movement::Stand)
+                .duration(self.surface_time * 0.7, self.surface_time * 1.3)
+                .to(Condition::StateTimeout, "dig"),
+            AiState::new("dig", Some("dig"), Movement::Stand)
+                .to(Condition::AnimationFinished, BURROWER_TUNNEL_STATE),
+            AiState::new(
+                BURROWER_TUNNEL_STATE,
+                None,
+                Movement::Navigate(self.tunnel_speed),
+            )
+            .to(Condition::PlayerWithin(self.erupt_range), "erupt")
+            .to(Condition::TimeInState(self.max_tunnel_time), "erupt"),
+            AiState::new("erupt", Some("erupt"), Movement::Stand)
+                .on_enter(AiAction::Shake(0.2, 2.))
+                .on_enter(AiAction::DamagePlayerWithin {
+                    range: self.erupt_damage_range,
+                    damage: 1.,
+                    knockback: 160.,
+                })
+                .on_enter(AiAction::Emit {
+                    pattern: "burrower_erupt",
+                    offset: Vec2::ZERO,
+                    spin: 0.,
+                })
+                .to(Condition::AnimationFinished, "surface"),
+        ])
+    }
+}
+
+pub fn spawn_burrower(data: &mut GameData, position: Vec2, ecs: &mut Ecs) -> Entity {
+    let id = data.new_entity();
+
+    let indexed_sprite = IndexedSprite::new(data, "burrower", 16, vec2(8., 10.));
+    let mut sprite = AnimatedSprite::new(
+        indexed_sprite,
+        HashMap::from([
+            ("idle".to_string(), Animation::new(vec![0, 1], 0.3, true)),
+            (
+                "dig".to_string(),
+                Animation::new(vec![9, 8, 7, 6], 0.1, false),
+            ),
+            (
+                "erupt".to_string(),
+                Animation::new(vec![6, 7, 8, 9, 2, 3], 0.08, false),
+            ),
+        ]),
+    );
+    sprite.set_animation("idle");
+    ecs.components.animated_sprites.insert(id, sprite);
+
+    let collider = CircleCollider {
+        radius: 4.,
+        coll_type: ColliderType::Enemy,
+    };
+    ecs.components.colliders.insert(id, collider);
+
+    ecs.components.positions.insert(id, position);
+    ecs.components.velocities.insert(id, Vec2::ZERO);
+
+    let burrower = Burrower {
+        tunnel_speed: 45.,
+        max_tunnel_time: 4.,
+        erupt_range: 10.,
+        erupt_damage_range: 22.,
+        surface_time: 1.6,
+        submerged: false,
+        dust_timer: Timer::new(0.12, true),
+    };
+    ecs.components
+        .ai_machines
+        .insert(id, AiMachine::new(burrower.behavior(), position));
+    ecs.components.burrowers.insert(id, burrower);
+
+    ecs.components.damageables.insert(
+        id,
+        Damageable {
+            invulnerable_timer: Some(Timer::new(0.2, false)),
+            hit_fx_timer: Some(Timer::new(0.22, false)),
+        },
+    );
+    ecs.components.health.insert(id, Health { hp: 40. });
+    ecs.components.knockback_resistance.insert(id, 0.4);
+    ecs.components.resistances.insert(
+        id,
+        Resistances::default()
+            .with(DamageType::Physical, 0.8)
+            .with(DamageType::Fire, 1.3),
+    );
+    ecs.components.damage_on_collision.insert(
+        id,
+        DamageOnCollision {
+            source: EntityType::Enemy,
+            damage: 1.,
+            damage_type: DamageType::Physical,
+            knockback: 100.,
+        },
+    );
+
+    ecs.components
+        .materials
+        .insert(id, create_sprite_color_material());
+
+    ecs.components.room_entity.insert(id, ());
+    ecs.components.enemies.insert(id, ());
+    ecs.components.aberration_increase.insert(id, 0.006);
+
+    ecs.entities.push(id);
+    id
+}
@@ -0,0 +1,95 @@
+use macroquad::prelude::*;
+
+use super::entity_id::Entity;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DamageType {
+    Physical,
+    Fire,
+    Frost,
+    Poison,
+    Aberrant,
+}
+
+impl DamageType {
+    pub fn color(&self) -> Color {
+        match self {
+            DamageType::Physical => WHITE,
+            DamageType::Fire => Color::from_rgba(223, 113, 38, 255),
+            DamageType::Frost => Color::from_rgba(99, 155, 255, 255),
+            DamageType::Poison => Color::from_rgba(106, 190, 48, 255),
+            DamageType::Aberrant => Color::from_rgba(215, 123, 186, 255),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Damage {
+    pub amount: f32,
+    pub kind: DamageType,
+    pub crit_chance: f32,
+    pub crit_multiplier: f32,
+}
+
+impl Damage {
+    pub fn new(amount: f32, kind: DamageType) -> Self {
+        Self {
+            amount,
+            kind,
+            crit_chance: 0.,
+            crit_multiplier: 1.,
+        }
+    }
+
+    pub fn physical(amount: f32) -> Self {
+        Self::new(amount, DamageType::Physical)
+    }
+
+    pub fn with_crit(mut self, chance: f32, multiplier: f32) -> Self {
+        self.crit_chance = chance;
+        self.crit_multiplier = multiplier;
+        self
+    }
+
+    /// Rolls for a crit and applies the target's resistances.
+    pub fn roll(&self, resistances: Option<&Resistances>) -> (f32, bool) {
+        let crit = self.crit_chance > 0. && rand::gen_range(0., 1.) < self.crit_chance;
+        let mut amount = self.amount;
+        if crit {
+            amount *= self.crit_multiplier;
+        }
+        if let Some(resistances) = resistances {
+            amount *= resistances.multiplier(self.kind);
+        }
+        (amount, crit)
+    }
+}
+
+/// Damage multipliers per type. Below 1 resists the type, above 1 is a vulnerability.
+#[derive(Clone, Default)]
+pub struct Resistances(pub Vec<(DamageType, f32)>);
+
+impl Resistances {
+    pub fn with(mut self, kind: DamageType, multiplier: f32) -> Self {
+        self.0.push((kind, multiplier));
+        self
+    }
+
+    pub fn multiplier(&self, kind: DamageType) -> f32 {
+        self.0
+            .iter()
+            .filter(|(k, _)| *k == kind)
+            .map(|(_, multiplier)| multiplier)
+            .product()
+    }
+}
+
+/// Damage that actually landed after crits and resistances.
+#[derive(Clone, Copy, Debug)]
+pub struct DamageDealt {
+    pub target: Entity,
+    pub position: Vec2,
+    pub amount: f32,
+    pub kind: DamageType,
+    pub crit: bool,
+}
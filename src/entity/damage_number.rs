@@ -0,0 +1,34 @@
+use macroquad::prelude::*;
+
+use crate::{game_data::GameData, timer::Timer};
+
+use super::{damage::DamageDealt, entities::Ecs, entity_id::Entity};
+
+pub struct DamageNumber {
+    pub target: Entity,
+    pub amount: f32,
+    pub color: Color,
+    pub crit: bool,
+}
+
+pub fn spawn_damage_number(data: &mut GameData, ecs: &mut Ecs, dealt: &DamageDealt) -> Entity {
+    let id = data.new_entity();
+
+    ecs.components.damage_numbers.insert(
+        id,
+        DamageNumber {
+            target: dealt.target,
+            amount: dealt.amount,
+            color: dealt.kind.color(),
+            crit: dealt.crit,
+        },
+    );
+    ecs.components
+        .positions
+        .insert(id, dealt.position + vec2(rand::gen_range(-4., 4.), -10.));
+    ecs.components.timers.insert(id, Timer::new(0.6, false));
+    ecs.components.room_entity.insert(id, ());
+
+    ecs.entities.push(id);
+    id
+}
@@ -0,0 +1,122 @@
+use macroquad::prelude::*;
+
+use crate::timer::Timer;
+
+use super::{entities::Ecs, entity_id::Entity};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EliteAffix {
+    /// Absorbs damage with a shield that regrows when not hit for a while.
+    Shielded,
+    Fast,
+    /// Splits into hoppers on death.
+    Splitting,
+    /// Bursts into a ring of bullets on death.
+    Explosive,
+    Regenerating,
+    /// Gives extra aberration when killed.
+    Aberrant,
+}
+
+impl EliteAffix {
+    pub const ALL: [EliteAffix; 6] = [
+        EliteAffix::Shielded,
+        EliteAffix::Fast,
+        EliteAffix::Splitting,
+        EliteAffix::Explosive,
+        EliteAffix::Regenerating,
+        EliteAffix::Aberrant,
+    ];
+
+    pub fn color(&self) -> Color {
+        match self {
+            EliteAffix::Shielded => Color::from_rgba(140, 200, 255, 255),
+            EliteAffix::Fast => Color::from_rgba(255, 230, 120, 255),
+            EliteAffix::Splitting => Color::from_rgba(200, 140, 255, 255),
+            EliteAffix::Explosive => Color::from_rgba(255, 120, 60, 255),
+            EliteAffix::Regenerating => Color::from_rgba(120, 255, 140, 255),
+            EliteAffix::Aberrant => Color::from_rgba(255, 80, 180, 255),
+        }
+    }
+
+    pub fn roll(count: usize) -> Vec<EliteAffix> {
+        let mut pool = EliteAffix::ALL.to_vec();
+        let mut affixes = vec![];
+        while affixes.len() < count && pool.len() > 0 {
+            affixes.push(pool.remove(rand::gen_range(0, pool.len())));
+        }
+        affixes
+    }
+}
+
+pub const ELITE_SPEED_MULTIPLIER: f32 = 1.5;
+pub const ELITE_REGEN_PER_SECOND: f32 = 0.04;
+
+pub struct Elite {
+    pub affixes: Vec<EliteAffix>,
+    pub max_hp: f32,
+    pub shield: f32,
+    pub max_shield: f32,
+    pub shield_regen_timer: Timer,
+}
+
+impl Elite {
+    pub fn has(&self, affix: EliteAffix) -> bool {
+        self.affixes.contains(&affix)
+    }
+
+    /// Soaks up damage with the shield first and returns what gets through.
+    pub fn absorb(&mut self, damage: f32) -> f32 {
+        if !self.has(EliteAffix::Shielded) {
+            return damage;
+        }
+        self.shield_regen_timer.reset();
+        let absorbed = damage.min(self.shield);
+        self.shield -= absorbed;
+        damage - absorbed
+    }
+
+    pub fn tint(&self) -> Color {
+        let blink = (get_time() * 4.).sin() as f32 * 0.5 + 0.5;
+        let color = self.affixes[0].color();
+        Color::new(
+            1. + (color.r - 1.) * (0.4 + 0.3 * blink),
+            1. + (color.g - 1.) * (0.4 + 0.3 * blink),
+            1. + (color.b - 1.) * (0.4 + 0.3 * blink),
+            1.,
+        )
+    }
+}
+
+pub fn make_elite(ecs: &mut Ecs, entity: Entity, affixes: Vec<EliteAffix>, hp_multiplier: f32) {
+    if affixes.len() == 0 {
+        return;
+    }
+    let Some(health) = ecs.components.health.get_mut(&entity) else {
+        return;
+    };
+    health.hp *= hp_multiplier;
+    let max_hp = health.hp;
+
+    if affixes.contains(&EliteAffix::Aberrant) {
+        if let Some(aberration) = ecs.components.aberration_increase.get_mut(&entity) {
+            *aberration *= 3.;
+        }
+    }
+
+    let max_shield = if affixes.contains(&EliteAffix::Shielded) {
+        max_hp * 0.5
+    } else {
+        0.
+    };
+    ecs.components.elites.insert(
+        entity,
+        Elite {
+            affixes,
+            max_hp,
+            shield: max_shield,
+            max_shield,
+            shield_regen_timer: Timer::new(3., false),
+        },
+    );
+}
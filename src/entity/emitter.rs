@@ -0,0 +1,36 @@
+use macroquad::prelude::*;
+
+use crate::{bullet_pattern::BulletPattern, timer::Timer};
+
+pub struct BulletEmitter {
+    pub pattern: BulletPattern,
+    /// Added to every shot angle, in radians.
+    pub rotation: f32,
+    pub offset: Vec2,
+    pub step: usize,
+    pub repeat: usize,
+    pub wait: f32,
+}
+
+impl BulletEmitter {
+    pub fn new(pattern: &BulletPattern, rotation: f32, offset: Vec2) -> Self {
+        Self {
+            wait: pattern.steps.first().map_or(0., |step| step.delay),
+            pattern: pattern.clone(),
+            rotation,
+            offset,
+            step: 0,
+            repeat: 0,
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.step >= self.pattern.steps.len()
+    }
+}
+
+pub struct SpeedCurve {
+    pub start_speed: f32,
+    pub end_speed: f32,
+    pub timer: Timer,
+}
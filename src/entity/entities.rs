@@ -1,16 +1,25 @@
 use super::{
+    ai::AiMachine,
     animated_sprite::AnimatedSprite,
+    burrower::Burrower,
+    damage::Resistances,
+    damage_number::DamageNumber,
+    elite::Elite,
+    emitter::{BulletEmitter, SpeedCurve},
     entity_id::Entity,
     hopper::Hopper,
     mirituhg::Mirituhg,
     pickup::Pickup,
     player::PlayerData,
+    projectile::{Homing, Pierce, SplitOnImpact},
+    spawn_marker::SpawnMarker,
     spawner::Spawner,
     spitter::Spitter,
+    status::{StatusEffect, StatusEffects},
     stomper::Stomper,
     tags::{DamageOnCollision, Damageable, DespawnOnAnimEnd, DespawnOnHit, Health},
 };
-use crate::{systems::collision::CircleCollider, timer::Timer};
+use crate::{items::weapon::WeaponBehavior, systems::collision::CircleCollider, timer::Timer};
 use macroquad::{material::Material, math::Vec2};
 use std::collections::HashMap;
 
@@ -26,9 +35,11 @@ pub struct Components {
     pub positions: ComponentColl<Vec2>,
     pub velocities: ComponentColl<Vec2>,
     pub spawners: ComponentColl<Spawner>,
+    pub spawn_markers: ComponentColl<SpawnMarker>,
     pub hoppers: ComponentColl<Hopper>,
     pub spitters: ComponentColl<Spitter>,
     pub stompers: ComponentColl<Stomper>,
+    pub burrowers: ComponentColl<Burrower>,
     pub damage_on_collision: ComponentColl<DamageOnCollision>,
     pub health: ComponentColl<Health>,
     pub materials: ComponentColl<Material>,
@@ -44,6 +55,23 @@ pub struct Components {
     pub aberration_increase: ComponentColl<f32>,
     pub mirituhg: ComponentColl<Mirituhg>,
     pub mirituhg_death: ComponentColl<()>,
+    pub bullet_emitters: ComponentColl<BulletEmitter>,
+    pub speed_curves: ComponentColl<SpeedCurve>,
+    pub ai_machines: ComponentColl<AiMachine>,
+    pub status_effects: ComponentColl<StatusEffects>,
+    pub status_on_hit: ComponentColl<Vec<StatusEffect>>,
+    pub knockbacks: ComponentColl<Vec2>,
+    pub knockback_resistance: ComponentColl<f32>,
+    pub resistances: ComponentColl<Resistances>,
+    pub elites: ComponentColl<Elite>,
+    pub pierces: ComponentColl<Pierce>,
+    pub bounces: ComponentColl<u32>,
+    pub homing: ComponentColl<Homing>,
+    pub split_on_impact: ComponentColl<SplitOnImpact>,
+    pub projectile_ranges: ComponentColl<f32>,
+    pub damage_numbers: ComponentColl<DamageNumber>,
+    /// Weapons the player swapped out, kept with their upgrades until picked up again.
+    pub dropped_weapons: ComponentColl<Box<dyn WeaponBehavior>>,
 }
 
 #[derive(Default)]
@@ -79,9 +107,11 @@ impl Ecs {
         self.components.positions.remove(entity);
         self.components.velocities.remove(entity);
         self.components.spawners.remove(entity);
+        self.components.spawn_markers.remove(entity);
         self.components.hoppers.remove(entity);
         self.components.spitters.remove(entity);
         self.components.stompers.remove(entity);
+        self.components.burrowers.remove(entity);
         self.components.damage_on_collision.remove(entity);
         self.components.health.remove(entity);
         self.components.materials.remove(entity);
@@ -97,5 +127,21 @@ impl Ecs {
         self.components.aberration_increase.remove(entity);
         self.components.mirituhg.remove(entity);
         self.components.mirituhg_death.remove(entity);
+        self.components.bullet_emitters.remove(entity);
+        self.components.speed_curves.remove(entity);
+        self.components.ai_machines.remove(entity);
+        self.components.status_effects.remove(entity);
+        self.components.status_on_hit.remove(entity);
+        self.components.knockbacks.remove(entity);
+        self.components.knockback_resistance.remove(entity);
+        self.components.resistances.remove(entity);
+        self.components.elites.remove(entity);
+        self.components.pierces.remove(entity);
+        self.components.bounces.remove(entity);
+        self.components.homing.remove(entity);
+        self.components.split_on_impact.remove(entity);
+        self.components.projectile_ranges.remove(entity);
+        self.components.damage_numbers.remove(entity);
+        self.components.dropped_weapons.remove(entity);
     }
 }
@@ -1,9 +1,15 @@
-use super::entity_id::Entity;
+use macroquad::math::Vec2;
+
+use super::{damage::Damage, entity_id::Entity, status::StatusEffect};
 
 pub struct DamageEvent {
     pub source: Entity,
     pub target: Entity,
-    pub damage: f32,
+    pub damage: Damage,
+    pub statuses: Vec<StatusEffect>,
+    pub impulse: Option<Vec2>,
+    /// Continuous damage like beams ignores invulnerability and doesn't cause hit reactions.
+    pub continuous: bool,
 }
 
 pub struct DeathEvent(pub Entity);
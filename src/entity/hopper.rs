@@ -10,19 +10,46 @@ use crate::{
 };
 
 use super::{
+    ai::{AiBehavior, AiMachine, AiState, Condition, Movement, Steering},
     animated_sprite::{AnimatedSprite, Animation},
+    damage::DamageType,
     entities::Ecs,
     entity_id::Entity,
     tags::{DamageOnCollision, Damageable, EntityType, Health},
 };
 
 pub struct Hopper {
-    pub jump_timer: Timer,
-    pub jumping: bool,
     pub move_speed: f32,
     pub jump_move_speed: f32,
 }
 
+impl Hopper {
+    pub fn behavior(&self) -> AiBehavior {
+        // Hoppers close in while circling, so a group ends up around the player.
+        let surround = || Movement::Steer {
+            speed: self.move_speed,
+            behaviors: vec![
+                (Steering::Seek, 1.),
+                (Steering::Orbit { radius: 28. }, 0.8),
+                (Steering::Separation { radius: 12. }, 1.2),
+            ],
+        };
+        AiBehavior::new(vec![
+            AiState::new("spawned", Some("move"), surround())
+                .to(Condition::TimeInState(2.), "jump"),
+            AiState::new("move", Some("move"), surround())
+                .duration(0.5, 1.5)
+                .to(Condition::StateTimeout, "jump"),
+            AiState::new(
+                "jump",
+                Some("jump"),
+                Movement::KeepHeading(self.jump_move_speed),
+            )
+            .to(Condition::TimeInState(0.96), "move"),
+        ])
+    }
+}
+
 pub fn spawn_hopper(data: &mut GameData, position: Vec2, ecs: &mut Ecs) -> Entity {
     let id = data.new_entity();
 
@@ -49,11 +76,12 @@ pub fn spawn_hopper(data: &mut GameData, position: Vec2, ecs: &mut Ecs) -> Entit
     ecs.components.velocities.insert(id, Vec2::ZERO);
 
     let hopper = Hopper {
-        jump_timer: Timer::new(2., false),
-        jumping: false,
         move_speed: 25.,
         jump_move_speed: 25.,
     };
+    ecs.components
+        .ai_machines
+        .insert(id, AiMachine::new(hopper.behavior(), position));
     ecs.components.hoppers.insert(id, hopper);
 
     ecs.components.damageables.insert(
@@ -64,11 +92,14 @@ pub fn spawn_hopper(data: &mut GameData, position: Vec2, ecs: &mut Ecs) -> Entit
         },
     );
     ecs.components.health.insert(id, Health { hp: 30. });
+    ecs.components.knockback_resistance.insert(id, 0.2);
     ecs.components.damage_on_collision.insert(
         id,
         DamageOnCollision {
             source: EntityType::Enemy,
             damage: 1.,
+            damage_type: DamageType::Physical,
+            knockback: 100.,
         },
     );
 
@@ -4,57 +4,187 @@ use macroquad::prelude::*;
 
 use crate::{
     game_data::GameData,
+    room::Enemy,
     sprite::{flash_material::create_sprite_color_material, indexed_sprite::IndexedSprite},
     systems::collision::{CircleCollider, ColliderType},
     timer::Timer,
 };
 
 use super::{
+    ai::{AiAction, AiBehavior, AiMachine, AiState, Condition, Movement},
     animated_sprite::{AnimatedSprite, Animation},
+    damage::{DamageType, Resistances},
     entities::Ecs,
     entity_id::Entity,
     tags::{DamageOnCollision, Damageable, EntityType, Health},
 };
 
-#[derive(Debug, PartialEq)]
-pub enum MiritughState {
-    Idle,
-    Spawn,
-    ShootTransition,
-    Shoot,
-    Dead,
-}
+/// Fractions of max hp below which the next phase starts.
+pub const MIRITUHG_PHASE_THRESHOLDS: [f32; 2] = [0.66, 0.33];
+const MIRITUHG_HP_PER_DEFEAT: f32 = 0.5;
 
 pub struct Mirituhg {
     pub move_speed: f32,
-    pub shoot_move_speed: f32,
+    pub charge_speed: f32,
     pub max_hp: f32,
+    pub intro_timer: Timer,
+    pub phase: usize,
+}
 
-    pub state: MiritughState,
-    pub previously_spawned: bool,
-    pub next_attack_timer: Timer,
-    pub shoot_timer: Timer,
+impl Mirituhg {
+    pub fn update(&mut self) {
+        self.intro_timer.update();
+    }
 
-    pub spawn_1_timer: Timer,
-    pub spawn_2_timer: Timer,
-    pub spawn_3_timer: Timer,
-    pub spawning: bool,
+    /// Pulsing colour cue so telegraphs sharing frames with other animations stay readable.
+    pub fn telegraph_tint(animation: &str) -> Option<Color> {
+        let color = match animation {
+            "telegraph_spiral" => Color::new(1.4, 1.2, 0.6, 1.),
+            "telegraph_aimed" => Color::new(1.4, 0.9, 0.5, 1.),
+            "telegraph_charge" => Color::new(1.6, 0.5, 0.5, 1.),
+            "telegraph_summon" => Color::new(1.1, 0.6, 1.5, 1.),
+            _ => return None,
+        };
+        let blink = (get_time() * 12.).sin() as f32 * 0.5 + 0.5;
+        Some(Color::new(
+            1. + (color.r - 1.) * blink,
+            1. + (color.g - 1.) * blink,
+            1. + (color.b - 1.) * blink,
+            1.,
+        ))
+    }
 
-    pub next_move_timer: Timer,
-    pub target_pos: Vec2,
+    pub fn behavior(&self) -> AiBehavior {
+        let [phase_1, phase_2] = MIRITUHG_PHASE_THRESHOLDS;
+        // Attacks come faster in later phases.
+        let attack_ready = |time: f32| {
+            vec![
+                (
+                    Condition::All(vec![
+                        Condition::HpBelow(phase_2),
+                        Condition::TimeInState(time - 2.),
+                    ]),
+                    vec![
+                        "classic",
+                        "telegraph_spiral",
+                        "telegraph_aimed",
+                        "telegraph_charge",
+                        "telegraph_summon",
+                    ],
+                ),
+                (
+                    Condition::All(vec![
+                        Condition::HpBelow(phase_1),
+                        Condition::TimeInState(time - 1.),
+                    ]),
+                    vec!["classic", "telegraph_spiral", "telegraph_aimed"],
+                ),
+                (Condition::TimeInState(time), vec!["classic"]),
+            ]
+        };
+        let wander = Movement::Wander {
+            speed: self.move_speed,
+            min_radius: 50.,
+            max_radius: 72.,
+            retarget_time: 2.5,
+        };
+        let telegraph = |name: &'static str, attack: &'static str| {
+            AiState::new(name, Some(name), Movement::Stand).to(Condition::AnimationFinished, attack)
+        };
+        let emit = |pattern: &'static str, spin: f32| AiAction::Emit {
+            pattern,
+            offset: Vec2::ZERO,
+            spin,
+        };
 
-    pub shoot_rotation: f32,
-}
+        let mut idle = AiState::new("idle", Some("idle"), wander);
+        for (condition, targets) in attack_ready(4.5) {
+            idle = idle.to_any(condition, targets);
+        }
+        let mut shoot = AiState::new(
+            "shoot",
+            None,
+            Movement::Wander {
+                speed: self.move_speed,
+                min_radius: 50.,
+                max_radius: 72.,
+                retarget_time: 2.5,
+            },
+        )
+        .every(0.25, emit("mirituhg_ring", 1.));
+        for (condition, _) in attack_ready(4.5) {
+            shoot = shoot.to(condition, "idle");
+        }
 
-impl Mirituhg {
-    pub fn update(&mut self) {
-        self.next_attack_timer.update();
-        self.shoot_timer.update();
-        self.spawn_1_timer.update();
-        self.spawn_2_timer.update();
-        self.spawn_3_timer.update();
-        self.next_move_timer.update();
-        self.shoot_rotation += 1. * get_frame_time();
+        AiBehavior::new(vec![
+            AiState::new("intro", Some("idle"), Movement::Stand)
+                .to(Condition::TimeInState(self.intro_timer.time), "idle"),
+            idle,
+            AiState::new("classic", None, Movement::Stand)
+                .to(
+                    Condition::EnteredMoreRecently("spawn", "shoot"),
+                    "shoot_approach",
+                )
+                .to(Condition::Always, "spawn_approach"),
+            AiState::new(
+                "spawn_approach",
+                Some("idle"),
+                Movement::MoveToAnchor(self.move_speed),
+            )
+            .to(Condition::NearAnchor(2.), "spawn"),
+            AiState::new("spawn", Some("spawn"), Movement::Stand)
+                .at(0.15 * 5., AiAction::SpawnEnemy(Enemy::Hopper, Vec2::ZERO))
+                .at(0.15 * 5., AiAction::SpawnEnemy(Enemy::Hopper, Vec2::ZERO))
+                .at(0.15 * 7., AiAction::SpawnEnemy(Enemy::Hopper, Vec2::ZERO))
+                .at(0.15 * 7., AiAction::SpawnEnemy(Enemy::Hopper, Vec2::ZERO))
+                .at(0.15 * 9., AiAction::SpawnEnemy(Enemy::Hopper, Vec2::ZERO))
+                .at(0.15 * 9., AiAction::SpawnEnemy(Enemy::Hopper, Vec2::ZERO))
+                .to(Condition::AnimationFinished, "idle"),
+            AiState::new(
+                "shoot_approach",
+                Some("idle"),
+                Movement::MoveToAnchor(self.move_speed),
+            )
+            .to(Condition::NearAnchor(2.), "shoot_transition"),
+            AiState::new(
+                "shoot_transition",
+                Some("shoot_transition"),
+                Movement::Stand,
+            )
+            .to(Condition::AnimationFinished, "shoot"),
+            shoot,
+            telegraph("telegraph_spiral", "spiral"),
+            AiState::new("spiral", Some("attack"), Movement::Stand)
+                .on_enter(emit("mirituhg_spiral", 0.))
+                .to(Condition::TimeInState(2.5), "idle"),
+            telegraph("telegraph_aimed", "aimed"),
+            AiState::new("aimed", Some("attack"), Movement::Stand)
+                .on_enter(emit("mirituhg_aimed_volley", 0.))
+                .to(Condition::TimeInState(1.4), "idle"),
+            telegraph("telegraph_charge", "charge").on_exit(AiAction::LockTarget),
+            AiState::new(
+                "charge",
+                Some("attack"),
+                Movement::MoveToLockedTarget(self.charge_speed),
+            )
+            .to(Condition::NearLockedTarget(4.), "slam")
+            .to(Condition::TimeInState(1.2), "slam"),
+            AiState::new("slam", None, Movement::Stand)
+                .on_enter(emit("mirituhg_slam", 0.))
+                .on_enter(AiAction::Shake(0.4, 3.))
+                .on_enter(AiAction::DamagePlayerWithin {
+                    range: 28.,
+                    damage: 1.,
+                    knockback: 220.,
+                })
+                .to(Condition::Always, "idle"),
+            telegraph("telegraph_summon", "summon"),
+            AiState::new("summon", None, Movement::Stand)
+                .on_enter(AiAction::SpawnEnemy(Enemy::Spitter, vec2(-40., 0.)))
+                .on_enter(AiAction::SpawnEnemy(Enemy::Spitter, vec2(40., 0.)))
+                .on_enter(AiAction::SpawnEnemy(Enemy::Stomper, vec2(0., 40.)))
+                .to(Condition::Always, "idle"),
+        ])
     }
 }
 
@@ -95,6 +225,26 @@ pub fn spawn_mirituhg(data: &mut GameData, position: Vec2, ecs: &mut Ecs) -> Ent
                     false,
                 ),
             ),
+            (
+                "telegraph_spiral".to_string(),
+                Animation::new(vec![20, 21, 22, 23, 24, 25, 24, 23, 22, 21], 0.08, false),
+            ),
+            (
+                "telegraph_aimed".to_string(),
+                Animation::new(vec![26, 27, 28, 27, 26, 27, 28], 0.1, false),
+            ),
+            (
+                "telegraph_charge".to_string(),
+                Animation::new(vec![4, 3, 4, 3, 4, 3, 4, 3, 4, 3, 4], 0.07, false),
+            ),
+            (
+                "telegraph_summon".to_string(),
+                Animation::new(vec![12, 11, 10, 11, 12, 11, 10, 11, 12], 0.1, false),
+            ),
+            (
+                "attack".to_string(),
+                Animation::new(vec![29, 30, 31, 32, 33], 0.1, true),
+            ),
         ]),
     );
     sprite.set_animation("idle");
@@ -108,23 +258,18 @@ pub fn spawn_mirituhg(data: &mut GameData, position: Vec2, ecs: &mut Ecs) -> Ent
     ecs.components.positions.insert(id, position);
     ecs.components.velocities.insert(id, Vec2::ZERO);
 
-    let hp = 900.;
+    // Every return of the boss is tougher than the last
+    let hp = 900. * (1. + MIRITUHG_HP_PER_DEFEAT * data.bosses_defeated as f32);
     let mirituhg = Mirituhg {
         move_speed: 35.,
-        shoot_move_speed: 15.,
+        charge_speed: 160.,
         max_hp: hp,
-        state: MiritughState::Idle,
-        previously_spawned: false,
-        spawning: false,
-        next_attack_timer: Timer::new(4.5, false),
-        shoot_timer: Timer::new(0.25, false),
-        spawn_1_timer: Timer::new(0.15 * 5., false),
-        spawn_2_timer: Timer::new(0.15 * 7., false),
-        spawn_3_timer: Timer::new(0.15 * 9., false),
-        next_move_timer: Timer::new(2.5, true),
-        target_pos: vec2(180., 120.),
-        shoot_rotation: 0.,
+        intro_timer: Timer::new(2.5, false),
+        phase: 0,
     };
+    ecs.components
+        .ai_machines
+        .insert(id, AiMachine::new(mirituhg.behavior(), position));
     ecs.components.mirituhg.insert(id, mirituhg);
 
     ecs.components.damageables.insert(
@@ -135,11 +280,20 @@ pub fn spawn_mirituhg(data: &mut GameData, position: Vec2, ecs: &mut Ecs) -> Ent
         },
     );
     ecs.components.health.insert(id, Health { hp });
+    ecs.components.knockback_resistance.insert(id, 0.95);
+    ecs.components.resistances.insert(
+        id,
+        Resistances::default()
+            .with(DamageType::Aberrant, 0.25)
+            .with(DamageType::Physical, 0.9),
+    );
     ecs.components.damage_on_collision.insert(
         id,
         DamageOnCollision {
             source: EntityType::Enemy,
             damage: 1.,
+            damage_type: DamageType::Aberrant,
+            knockback: 200.,
         },
     );
 
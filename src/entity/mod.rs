@@ -1,4 +1,10 @@
+pub mod ai;
 pub mod animated_sprite;
+pub mod burrower;
+pub mod damage;
+pub mod damage_number;
+pub mod elite;
+pub mod emitter;
 pub mod entities;
 pub mod entity_id;
 pub mod events;
@@ -9,8 +15,11 @@ pub mod pickup;
 pub mod player;
 pub mod projectile;
 pub mod skull;
+pub mod spawn_marker;
 pub mod spawner;
 pub mod spitter;
+pub mod status;
 pub mod stomper;
+pub mod synergy;
 pub mod tags;
 pub mod upgrades;
@@ -4,6 +4,7 @@ use macroquad::prelude::*;
 
 use crate::{
     game_data::GameData,
+    items::weapon::WeaponType,
     sprite::{flash_material::create_sprite_color_material, indexed_sprite::IndexedSprite},
     systems::collision::{CircleCollider, ColliderType},
 };
@@ -20,25 +21,34 @@ pub enum Pickup {
     Health(f32),
     AnomalyBig,
     AnomalySmall,
+    Weapon(WeaponType),
 }
 
 pub fn spawn_pickup(data: &mut GameData, position: Vec2, ecs: &mut Ecs, pickup: Pickup) -> Entity {
     let id = data.new_entity();
 
+    // Weapon pickups are drawn with their upgrade icon instead of a sprite
     let texture = match pickup {
-        Pickup::Health(_) => "health",
-        Pickup::AnomalyBig => "anomaly_big",
-        Pickup::AnomalySmall => "anomaly_small",
+        Pickup::Health(_) => Some("health"),
+        Pickup::AnomalyBig => Some("anomaly_big"),
+        Pickup::AnomalySmall => Some("anomaly_small"),
+        Pickup::Weapon(_) => None,
     };
-    let indexed_sprite = IndexedSprite::new(data, texture, 16, vec2(8., 9.));
-    let sprite = AnimatedSprite::new(
-        indexed_sprite,
-        HashMap::from([("noop".to_string(), Animation::new(vec![0], 0., false))]),
-    );
-    ecs.components.animated_sprites.insert(id, sprite);
+    if let Some(texture) = texture {
+        let indexed_sprite = IndexedSprite::new(data, texture, 16, vec2(8., 9.));
+        let sprite = AnimatedSprite::new(
+            indexed_sprite,
+            HashMap::from([("noop".to_string(), Animation::new(vec![0], 0., false))]),
+        );
+        ecs.components.animated_sprites.insert(id, sprite);
+    }
 
     let collider = CircleCollider {
-        radius: 3.,
+        radius: if let Pickup::Weapon(_) = pickup {
+            6.
+        } else {
+            3.
+        },
         coll_type: ColliderType::Pickup,
     };
     ecs.components.colliders.insert(id, collider);
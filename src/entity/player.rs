@@ -1,6 +1,7 @@
 use crate::{
     game_data::GameData,
     sprite::{flash_material::create_sprite_color_material, indexed_sprite::IndexedSprite},
+    stats::{Stat, Stats},
     systems::collision::{CircleCollider, ColliderType},
     timer::Timer,
 };
@@ -16,36 +17,34 @@ use super::{
 };
 
 pub struct PlayerData {
-    pub base_move_speed: f32,
+    pub stats: Stats,
     pub sprite_offset: Vec2,
-    pub base_max_hp: u8,
     pub aberration: f32,
     pub aberration_increase_timer: Timer,
     pub shadows: Vec<Entity>,
     pub upgrades: Vec<CommonUpgrade>,
+    pub last_safe_position: Vec2,
 }
 
 pub struct PlayerUpgradeData {
     pub move_speed: f32,
+    pub crit_chance: f32,
+    pub crit_multiplier: f32,
     pub max_hp: u8,
 }
 
 impl PlayerData {
-    pub fn get_upgraded_data(&self) -> PlayerUpgradeData {
-        let mut max_hp = self.base_max_hp;
-        let mut move_speed_percentage_increase = 0.;
-
-        for upgrade in &self.upgrades {
-            match upgrade {
-                CommonUpgrade::MaxHp(increase) => max_hp += increase,
-                CommonUpgrade::MoveSpeed(increase) => move_speed_percentage_increase += increase,
-                _ => {}
-            }
-        }
+    pub fn add_upgrade(&mut self, upgrade: CommonUpgrade) {
+        self.stats.add(upgrade.modifiers());
+        self.upgrades.push(upgrade);
+    }
 
+    pub fn get_upgraded_data(&self) -> PlayerUpgradeData {
         PlayerUpgradeData {
-            max_hp,
-            move_speed: self.base_move_speed * (1. + move_speed_percentage_increase),
+            max_hp: self.stats.get(Stat::MaxHp) as u8,
+            crit_chance: self.stats.get(Stat::CritChance),
+            crit_multiplier: self.stats.get(Stat::CritMultiplier),
+            move_speed: self.stats.get(Stat::MoveSpeed),
         }
     }
 }
@@ -104,18 +103,23 @@ pub fn spawn_player(data: &mut GameData, ecs: &mut Ecs) -> Entity {
     ecs.components.velocities.insert(id, Vec2::ZERO);
 
     let player_data = PlayerData {
-        base_move_speed: 72.,
+        stats: Stats::new(&[
+            (Stat::MaxHp, 3.),
+            (Stat::MoveSpeed, 72.),
+            (Stat::CritChance, 0.05),
+            (Stat::CritMultiplier, 1.5),
+        ]),
         sprite_offset: vec2(8., 10.),
-        base_max_hp: 3,
         aberration: 0.,
         aberration_increase_timer: Timer::new(0.2, true),
         shadows: vec![shadow1_id, shadow2_id],
         upgrades: vec![],
+        last_safe_position: vec2(180., 120.),
     };
     ecs.components.health.insert(
         id,
         Health {
-            hp: player_data.base_max_hp.into(),
+            hp: player_data.stats.get(Stat::MaxHp),
         },
     );
     ecs.components.player_data.insert(id, player_data);
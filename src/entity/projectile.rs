@@ -8,6 +8,7 @@ use std::collections::HashMap;
 
 use super::{
     animated_sprite::{AnimatedSprite, Animation},
+    damage::DamageType,
     entities::Ecs,
     entity_id::Entity,
     tags::{DamageOnCollision, DespawnOnHit, EntityType},
@@ -51,6 +52,12 @@ pub fn spawn_bullet(
         id,
         DamageOnCollision {
             damage,
+            damage_type: DamageType::Physical,
+            knockback: if target == EntityType::Player {
+                60.
+            } else {
+                40.
+            },
             source: if target == EntityType::Player {
                 EntityType::Enemy
             } else {
@@ -65,3 +72,62 @@ pub fn spawn_bullet(
     ecs.entities.push(id);
     id
 }
+
+/// Bullets pass through this many damageable targets before breaking.
+pub struct Pierce {
+    pub remaining: u32,
+    pub hit: Vec<Entity>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Homing {
+    /// Radians per second.
+    pub turn_speed: f32,
+    pub range: f32,
+}
+
+/// Bursts into a ring of smaller bullets when the bullet breaks on something.
+#[derive(Clone, Copy, Debug)]
+pub struct SplitOnImpact {
+    pub count: usize,
+    pub speed: f32,
+    pub damage_multiplier: f32,
+}
+
+/// Optional bullet behaviours that can be combined freely.
+#[derive(Clone, Debug, Default)]
+pub struct ProjectileModifiers {
+    pub pierce: u32,
+    /// Ricochets off map tiles.
+    pub bounces: u32,
+    pub homing: Option<Homing>,
+    pub split: Option<SplitOnImpact>,
+    /// Distance after which the bullet fizzles out.
+    pub range: Option<f32>,
+}
+
+impl ProjectileModifiers {
+    pub fn apply(&self, ecs: &mut Ecs, bullet: Entity) {
+        if self.pierce > 0 {
+            ecs.components.pierces.insert(
+                bullet,
+                Pierce {
+                    remaining: self.pierce,
+                    hit: vec![],
+                },
+            );
+        }
+        if self.bounces > 0 {
+            ecs.components.bounces.insert(bullet, self.bounces);
+        }
+        if let Some(homing) = self.homing {
+            ecs.components.homing.insert(bullet, homing);
+        }
+        if let Some(split) = self.split {
+            ecs.components.split_on_impact.insert(bullet, split);
+        }
+        if let Some(range) = self.range {
+            ecs.components.projectile_ranges.insert(bullet, range);
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use macroquad::prelude::*;
+
+use crate::{game_data::GameData, room::Enemy, timer::Timer};
+
+use super::{entities::Ecs, entity_id::Entity};
+
+pub struct SpawnMarker {
+    pub enemy: Enemy,
+    pub timer: Timer,
+}
+
+pub fn spawn_spawn_marker(
+    data: &mut GameData,
+    ecs: &mut Ecs,
+    position: Vec2,
+    enemy: Enemy,
+    warning_time: f32,
+) -> Entity {
+    let id = data.new_entity();
+
+    ecs.components.positions.insert(id, position);
+    ecs.components.spawn_markers.insert(
+        id,
+        SpawnMarker {
+            enemy,
+            timer: Timer::new(warning_time, false),
+        },
+    );
+    ecs.components.room_entity.insert(id, ());
+
+    ecs.entities.push(id);
+    id
+}
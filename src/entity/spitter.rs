@@ -10,15 +10,80 @@ use crate::{
 };
 
 use super::{
+    ai::{AiAction, AiBehavior, AiMachine, AiState, Condition, Movement, Steering},
     animated_sprite::{AnimatedSprite, Animation},
+    damage::{DamageType, Resistances},
     entities::Ecs,
     entity_id::Entity,
     tags::{DamageOnCollision, Damageable, EntityType, Health},
 };
 
 pub struct Spitter {
-    pub attack_timer: Timer,
-    pub spit_timer: Timer,
+    pub attack_cooldown: f32,
+    pub spit_delay: f32,
+    pub move_speed: f32,
+    pub preferred_range: f32,
+}
+
+impl Spitter {
+    pub fn behavior(&self) -> AiBehavior {
+        let can_spit = || {
+            Condition::All(vec![
+                Condition::LineOfSight(true),
+                Condition::SinceEntered("spit", self.attack_cooldown),
+            ])
+        };
+        AiBehavior::new(vec![
+            AiState::new(
+                "watch",
+                Some("idle"),
+                Movement::Steer {
+                    speed: self.move_speed,
+                    behaviors: vec![
+                        (
+                            Steering::Arrive {
+                                distance: self.preferred_range,
+                                slowing: 20.,
+                            },
+                            1.,
+                        ),
+                        (
+                            Steering::Flee {
+                                within: self.preferred_range - 20.,
+                            },
+                            1.5,
+                        ),
+                        (
+                            Steering::Orbit {
+                                radius: self.preferred_range,
+                            },
+                            0.4,
+                        ),
+                        (Steering::Separation { radius: 16. }, 1.),
+                    ],
+                },
+            )
+            .to(can_spit(), "spit")
+            .to(Condition::LineOfSight(false), "approach"),
+            AiState::new(
+                "approach",
+                Some("idle"),
+                Movement::Navigate(self.move_speed),
+            )
+            .to(can_spit(), "spit")
+            .to(Condition::LineOfSight(true), "watch"),
+            AiState::new("spit", Some("spit"), Movement::Stand)
+                .at(
+                    self.spit_delay,
+                    AiAction::Emit {
+                        pattern: "spitter_shot",
+                        offset: vec2(0., -5.),
+                        spin: 0.,
+                    },
+                )
+                .to(Condition::AnimationFinished, "watch"),
+        ])
+    }
 }
 
 pub fn spawn_spitter(data: &mut GameData, position: Vec2, ecs: &mut Ecs) -> Entity {
@@ -51,9 +116,14 @@ pub fn spawn_spitter(data: &mut GameData, position: Vec2, ecs: &mut Ecs) -> Enti
     ecs.components.velocities.insert(id, Vec2::ZERO);
 
     let spitter = Spitter {
-        attack_timer: Timer::new(2., false),
-        spit_timer: Timer::new(0.36, false),
+        attack_cooldown: 2.,
+        spit_delay: 0.36,
+        move_speed: 20.,
+        preferred_range: 70.,
     };
+    ecs.components
+        .ai_machines
+        .insert(id, AiMachine::new(spitter.behavior(), position));
     ecs.components.spitters.insert(id, spitter);
 
     ecs.components.damageables.insert(
@@ -64,11 +134,20 @@ pub fn spawn_spitter(data: &mut GameData, position: Vec2, ecs: &mut Ecs) -> Enti
         },
     );
     ecs.components.health.insert(id, Health { hp: 30. });
+    ecs.components.knockback_resistance.insert(id, 0.4);
+    ecs.components.resistances.insert(
+        id,
+        Resistances::default()
+            .with(DamageType::Poison, 0.5)
+            .with(DamageType::Frost, 1.3),
+    );
     ecs.components.damage_on_collision.insert(
         id,
         DamageOnCollision {
             source: EntityType::Enemy,
             damage: 1.,
+            damage_type: DamageType::Physical,
+            knockback: 80.,
         },
     );
 
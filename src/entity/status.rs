@@ -0,0 +1,188 @@
+use macroquad::prelude::*;
+
+use crate::timer::Timer;
+
+use super::damage::{Damage, DamageType};
+
+const TICK_INTERVAL: f32 = 0.5;
+const SLOW_STACKS_TO_FREEZE: u32 = 3;
+const FREEZE_FROM_SLOW_DURATION: f32 = 1.2;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StatusKind {
+    /// Potency is the fraction of speed removed.
+    Slow,
+    /// Potency is damage per second. Reapplying refreshes and keeps the stronger burn.
+    Burn,
+    /// Potency is damage per second per stack.
+    Poison,
+    Stun,
+    Freeze,
+}
+
+impl StatusKind {
+    pub fn max_stacks(&self) -> u32 {
+        match self {
+            StatusKind::Poison => 5,
+            StatusKind::Slow => SLOW_STACKS_TO_FREEZE,
+            _ => 1,
+        }
+    }
+
+    /// Type of the damage dealt by ticks, if the status deals any.
+    pub fn damage_type(&self) -> Option<DamageType> {
+        match self {
+            StatusKind::Burn => Some(DamageType::Fire),
+            StatusKind::Poison => Some(DamageType::Poison),
+            _ => None,
+        }
+    }
+
+    pub fn tint(&self) -> Color {
+        match self {
+            StatusKind::Slow => Color::new(0.7, 0.8, 1., 1.),
+            StatusKind::Burn => Color::new(1., 0.6, 0.4, 1.),
+            StatusKind::Poison => Color::new(0.6, 1., 0.5, 1.),
+            StatusKind::Stun => Color::new(1., 1., 0.5, 1.),
+            StatusKind::Freeze => Color::new(0.5, 0.8, 1.4, 1.),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub duration: f32,
+    pub potency: f32,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusKind, duration: f32, potency: f32) -> Self {
+        Self {
+            kind,
+            duration,
+            potency,
+        }
+    }
+}
+
+pub struct ActiveStatus {
+    pub kind: StatusKind,
+    pub potency: f32,
+    pub stacks: u32,
+    pub timer: Timer,
+    pub tick_timer: Timer,
+}
+
+impl ActiveStatus {
+    pub fn tick_damage(&self) -> f32 {
+        match self.kind {
+            StatusKind::Burn | StatusKind::Poison => {
+                self.potency * self.stacks as f32 * TICK_INTERVAL
+            }
+            _ => 0.,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct StatusEffects {
+    pub active: Vec<ActiveStatus>,
+}
+
+impl StatusEffects {
+    pub fn apply(&mut self, effect: StatusEffect) {
+        // Fire and ice cancel each other out
+        let opposite = match effect.kind {
+            StatusKind::Burn => Some(StatusKind::Freeze),
+            StatusKind::Freeze => Some(StatusKind::Burn),
+            _ => None,
+        };
+        if let Some(opposite) = opposite {
+            if self.has(opposite) {
+                self.active.retain(|status| status.kind != opposite);
+                return;
+            }
+        }
+
+        if effect.kind == StatusKind::Slow && self.has(StatusKind::Freeze) {
+            return;
+        }
+
+        match self.active.iter_mut().find(|s| s.kind == effect.kind) {
+            Some(status) => match effect.kind {
+                // Stuns don't refresh while active so they can't be chained forever
+                StatusKind::Stun => {}
+                _ => {
+                    status.stacks = (status.stacks + 1).min(effect.kind.max_stacks());
+                    status.potency = status.potency.max(effect.potency);
+                    status.timer.time = status.timer.time.max(effect.duration);
+                    status.timer.reset();
+                }
+            },
+            None => {
+                self.active.push(ActiveStatus {
+                    kind: effect.kind,
+                    potency: effect.potency,
+                    stacks: 1,
+                    timer: Timer::new(effect.duration, false),
+                    tick_timer: Timer::new(TICK_INTERVAL, true),
+                });
+            }
+        }
+
+        let frozen_over = self
+            .active
+            .iter()
+            .any(|s| s.kind == StatusKind::Slow && s.stacks >= SLOW_STACKS_TO_FREEZE);
+        if frozen_over {
+            self.active.retain(|s| s.kind != StatusKind::Slow);
+            self.apply(StatusEffect::new(
+                StatusKind::Freeze,
+                FREEZE_FROM_SLOW_DURATION,
+                0.,
+            ));
+        }
+    }
+
+    /// Advances all effects and returns the damage dealt by ticks this frame.
+    pub fn update(&mut self) -> Vec<Damage> {
+        let mut damage = vec![];
+        for status in &mut self.active {
+            status.timer.update();
+            status.tick_timer.update();
+            if let (true, Some(kind)) = (
+                status.tick_timer.just_completed(),
+                status.kind.damage_type(),
+            ) {
+                damage.push(Damage::new(status.tick_damage(), kind));
+            }
+        }
+        self.active.retain(|status| !status.timer.completed());
+        damage
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.active.iter().any(|status| status.kind == kind)
+    }
+
+    pub fn disables_ai(&self) -> bool {
+        self.has(StatusKind::Stun) || self.has(StatusKind::Freeze)
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.disables_ai() {
+            return 0.;
+        }
+        self.active
+            .iter()
+            .filter(|status| status.kind == StatusKind::Slow)
+            .map(|status| 1. - (status.potency * status.stacks as f32).min(0.8))
+            .product()
+    }
+
+    /// Tint of the most recently applied effect.
+    pub fn tint(&self) -> Option<Color> {
+        self.active.last().map(|status| status.kind.tint())
+    }
+}
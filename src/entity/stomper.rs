@@ -10,18 +10,60 @@ use crate::{
 };
 
 use super::{
+    ai::{AiAction, AiBehavior, AiMachine, AiState, Condition, Movement, Steering},
     animated_sprite::{AnimatedSprite, Animation},
+    damage::{DamageType, Resistances},
     entities::Ecs,
     entity_id::Entity,
     tags::{DamageOnCollision, Damageable, EntityType, Health},
 };
 
 pub struct Stomper {
-    pub damage_timer: Timer,
-    pub jump_timer: Timer,
+    pub jump_cooldown: f32,
+    pub jump_range: f32,
+    pub damage_delay: f32,
+    pub damage_range: f32,
     pub move_speed: f32,
     pub jump_move_speed: f32,
-    pub jumping: bool,
+}
+
+impl Stomper {
+    pub fn behavior(&self) -> AiBehavior {
+        AiBehavior::new(vec![
+            AiState::new(
+                "walk",
+                Some("walk"),
+                Movement::Steer {
+                    speed: self.move_speed,
+                    behaviors: vec![
+                        (Steering::Seek, 1.),
+                        (Steering::Separation { radius: 20. }, 1.),
+                    ],
+                },
+            )
+            .to(
+                Condition::All(vec![
+                    Condition::PlayerWithin(self.jump_range),
+                    Condition::TimeInState(self.jump_cooldown),
+                ]),
+                "jump",
+            ),
+            AiState::new(
+                "jump",
+                Some("jump"),
+                Movement::Navigate(self.jump_move_speed),
+            )
+            .at(
+                self.damage_delay,
+                AiAction::DamagePlayerWithin {
+                    range: self.damage_range,
+                    damage: 1.,
+                    knockback: 180.,
+                },
+            )
+            .to(Condition::AnimationFinished, "walk"),
+        ])
+    }
 }
 
 pub fn spawn_stomper(data: &mut GameData, position: Vec2, ecs: &mut Ecs) -> Entity {
@@ -54,12 +96,16 @@ pub fn spawn_stomper(data: &mut GameData, position: Vec2, ecs: &mut Ecs) -> Enti
     ecs.components.velocities.insert(id, Vec2::ZERO);
 
     let stomper = Stomper {
-        damage_timer: Timer::new(0.13 * 8., false),
-        jump_timer: Timer::new(2.5, false),
+        jump_cooldown: 2.5,
+        jump_range: 36.,
+        damage_delay: 0.13 * 8.,
+        damage_range: 26.,
         move_speed: 34.,
         jump_move_speed: 16.,
-        jumping: false,
     };
+    ecs.components
+        .ai_machines
+        .insert(id, AiMachine::new(stomper.behavior(), position));
     ecs.components.stompers.insert(id, stomper);
 
     ecs.components.damageables.insert(
@@ -70,11 +116,20 @@ pub fn spawn_stomper(data: &mut GameData, position: Vec2, ecs: &mut Ecs) -> Enti
         },
     );
     ecs.components.health.insert(id, Health { hp: 70. });
+    ecs.components.knockback_resistance.insert(id, 0.8);
+    ecs.components.resistances.insert(
+        id,
+        Resistances::default()
+            .with(DamageType::Physical, 0.75)
+            .with(DamageType::Fire, 1.25),
+    );
     ecs.components.damage_on_collision.insert(
         id,
         DamageOnCollision {
             source: EntityType::Enemy,
             damage: 1.,
+            damage_type: DamageType::Physical,
+            knockback: 160.,
         },
     );
 
@@ -0,0 +1,147 @@
+use super::upgrades::{
+    BallsUpgrade, BeamUpgrade, DashUpgrade, LauncherUpgrade, MeleeUpgrade, WeaponUpgrade,
+};
+
+/// Owning every required upgrade unlocks the evolution in the upgrade offers.
+pub struct Synergy {
+    /// Shown on the upgrade card, so it has to stay short.
+    pub label: &'static str,
+    /// Upgrade kinds and how many of each are needed. Values inside the upgrades are ignored.
+    pub requires: Vec<(WeaponUpgrade, usize)>,
+    pub evolution: WeaponUpgrade,
+}
+
+impl Synergy {
+    pub fn all() -> Vec<Synergy> {
+        vec![
+            Synergy {
+                label: "Double+Rate",
+                requires: vec![
+                    (WeaponUpgrade::Launcher(LauncherUpgrade::DoubleBullet), 1),
+                    (WeaponUpgrade::Launcher(LauncherUpgrade::FireRate(0.)), 2),
+                ],
+                evolution: WeaponUpgrade::Launcher(LauncherUpgrade::Barrage),
+            },
+            Synergy {
+                label: "Split+Ball",
+                requires: vec![
+                    (WeaponUpgrade::Balls(BallsUpgrade::Split), 1),
+                    (WeaponUpgrade::Balls(BallsUpgrade::Amount(0)), 2),
+                ],
+                evolution: WeaponUpgrade::Balls(BallsUpgrade::Nova),
+            },
+            Synergy {
+                label: "Bullet+Dmg",
+                requires: vec![
+                    (WeaponUpgrade::Dash(DashUpgrade::Bullets), 1),
+                    (WeaponUpgrade::Dash(DashUpgrade::Damage(0.)), 2),
+                ],
+                evolution: WeaponUpgrade::Dash(DashUpgrade::Comet),
+            },
+            Synergy {
+                label: "Fire+Range",
+                requires: vec![
+                    (WeaponUpgrade::Beam(BeamUpgrade::Ignite), 1),
+                    (WeaponUpgrade::Beam(BeamUpgrade::Range(0.)), 1),
+                ],
+                evolution: WeaponUpgrade::Beam(BeamUpgrade::Inferno),
+            },
+            Synergy {
+                label: "Shock+Stun",
+                requires: vec![
+                    (WeaponUpgrade::Melee(MeleeUpgrade::Shockwave), 1),
+                    (WeaponUpgrade::Melee(MeleeUpgrade::Stun(0.)), 1),
+                ],
+                evolution: WeaponUpgrade::Melee(MeleeUpgrade::Quake),
+            },
+        ]
+    }
+
+    pub fn for_evolution(evolution: &WeaponUpgrade) -> Option<Synergy> {
+        Synergy::all()
+            .into_iter()
+            .find(|synergy| synergy.evolution.same_kind(evolution))
+    }
+
+    /// Whether `owned` fulfills the requirements and doesn't contain the evolution yet.
+    pub fn unlocked(&self, owned: &[WeaponUpgrade]) -> bool {
+        let count = |kind: &WeaponUpgrade| owned.iter().filter(|u| u.same_kind(kind)).count();
+        count(&self.evolution) == 0
+            && self
+                .requires
+                .iter()
+                .all(|(kind, amount)| count(kind) >= *amount)
+    }
+}
+
+pub fn unlocked_evolutions(owned: &[WeaponUpgrade]) -> Vec<WeaponUpgrade> {
+    Synergy::all()
+        .into_iter()
+        .filter(|synergy| synergy.unlocked(owned))
+        .map(|synergy| synergy.evolution)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn barrage() -> Synergy {
+        Synergy::for_evolution(&WeaponUpgrade::Launcher(LauncherUpgrade::Barrage)).unwrap()
+    }
+
+    #[test]
+    fn upgrade_values_are_ignored() {
+        let owned = vec![
+            WeaponUpgrade::Launcher(LauncherUpgrade::DoubleBullet),
+            WeaponUpgrade::Launcher(LauncherUpgrade::FireRate(0.1)),
+            WeaponUpgrade::Launcher(LauncherUpgrade::FireRate(0.25)),
+        ];
+        assert!(barrage().unlocked(&owned));
+    }
+
+    #[test]
+    fn counts_must_be_reached() {
+        let owned = vec![
+            WeaponUpgrade::Launcher(LauncherUpgrade::DoubleBullet),
+            WeaponUpgrade::Launcher(LauncherUpgrade::FireRate(0.1)),
+        ];
+        assert!(!barrage().unlocked(&owned));
+
+        let owned = vec![
+            WeaponUpgrade::Launcher(LauncherUpgrade::FireRate(0.1)),
+            WeaponUpgrade::Launcher(LauncherUpgrade::FireRate(0.1)),
+            WeaponUpgrade::Launcher(LauncherUpgrade::Damage(1.)),
+        ];
+        assert!(!barrage().unlocked(&owned));
+    }
+
+    #[test]
+    fn owned_evolution_stays_locked() {
+        let owned = vec![
+            WeaponUpgrade::Launcher(LauncherUpgrade::DoubleBullet),
+            WeaponUpgrade::Launcher(LauncherUpgrade::FireRate(0.1)),
+            WeaponUpgrade::Launcher(LauncherUpgrade::FireRate(0.1)),
+            WeaponUpgrade::Launcher(LauncherUpgrade::Barrage),
+        ];
+        assert!(!barrage().unlocked(&owned));
+        assert!(unlocked_evolutions(&owned).is_empty());
+    }
+
+    #[test]
+    fn other_weapons_dont_count() {
+        let owned = vec![
+            WeaponUpgrade::Beam(BeamUpgrade::Ignite),
+            WeaponUpgrade::Melee(MeleeUpgrade::Range(1.)),
+        ];
+        assert!(unlocked_evolutions(&owned).is_empty());
+    }
+
+    #[test]
+    fn every_evolution_has_one_synergy() {
+        for synergy in Synergy::all() {
+            assert!(synergy.evolution.is_evolution());
+            assert!(Synergy::for_evolution(&synergy.evolution).is_some());
+        }
+    }
+}
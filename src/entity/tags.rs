@@ -1,5 +1,7 @@
 use crate::timer::Timer;
 
+use super::damage::DamageType;
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum EntityType {
     Player,
@@ -10,6 +12,8 @@ pub enum EntityType {
 pub struct DamageOnCollision {
     pub source: EntityType,
     pub damage: f32,
+    pub damage_type: DamageType,
+    pub knockback: f32,
 }
 
 pub struct Health {
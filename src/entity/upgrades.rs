@@ -1,10 +1,15 @@
+use std::mem::discriminant;
+
 use macroquad::prelude::rand;
 
 use crate::{
     game_data::GameData,
-    items::weapon::{Weapon, WeaponType},
+    items::weapon::{WeaponBehavior, WeaponType},
+    stats::{Stat, StatModifier},
 };
 
+use super::synergy::{unlocked_evolutions, Synergy};
+
 pub struct UpgradeDescription {
     pub texture_name: String,
     pub text: Vec<String>,
@@ -42,11 +47,26 @@ impl Upgrade {
                 WeaponType::Launcher => UpgradeDescription::new("upgrade_launcher", "Launcher"),
                 WeaponType::Balls => UpgradeDescription::new("upgrade_balls", "Balls"),
                 WeaponType::Dash => UpgradeDescription::new("upgrade_dash", "Dash"),
+                WeaponType::Beam => UpgradeDescription::new("upgrade_beam", "Beam"),
+                WeaponType::Melee => UpgradeDescription::new("upgrade_melee", "Melee"),
             },
             Upgrade::WeaponUpgrade(upgrade) => upgrade.description(),
             Upgrade::CommonUpgrade(upgrade) => upgrade.description(),
         }
     }
+
+    /// Same upgrade, ignoring the values inside.
+    pub fn same_kind(&self, other: &Upgrade) -> bool {
+        match (self, other) {
+            (Upgrade::Item(a), Upgrade::Item(b)) => discriminant(a) == discriminant(b),
+            (Upgrade::Weapon(a), Upgrade::Weapon(b)) => a == b,
+            (Upgrade::WeaponUpgrade(a), Upgrade::WeaponUpgrade(b)) => a.same_kind(b),
+            (Upgrade::CommonUpgrade(a), Upgrade::CommonUpgrade(b)) => {
+                discriminant(a) == discriminant(b)
+            }
+            _ => false,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -77,9 +97,25 @@ pub enum CommonUpgrade {
     MaxHp(u8),
     MoveSpeed(f32),
     ItemDropChance(i32),
+    CritChance(f32),
+    CritDamage(f32),
 }
 
 impl CommonUpgrade {
+    pub fn modifiers(&self) -> Vec<StatModifier> {
+        match self {
+            CommonUpgrade::MaxHp(hp) => vec![StatModifier::flat(Stat::MaxHp, *hp as f32)],
+            CommonUpgrade::MoveSpeed(speed) => vec![StatModifier::percent(Stat::MoveSpeed, *speed)],
+            CommonUpgrade::ItemDropChance(_) => vec![],
+            CommonUpgrade::CritChance(chance) => {
+                vec![StatModifier::flat(Stat::CritChance, *chance)]
+            }
+            CommonUpgrade::CritDamage(multiplier) => {
+                vec![StatModifier::flat(Stat::CritMultiplier, *multiplier)]
+            }
+        }
+    }
+
     pub fn description(&self) -> UpgradeDescription {
         match self {
             CommonUpgrade::MaxHp(hp) => UpgradeDescription::new_with_line2(
@@ -95,6 +131,16 @@ impl CommonUpgrade {
             CommonUpgrade::ItemDropChance(_) => {
                 UpgradeDescription::new_with_line2("upgrade_items", "Inc. Item", "Drops")
             }
+            CommonUpgrade::CritChance(chance) => UpgradeDescription::new_with_line2(
+                "upgrade_crit",
+                format!("+ {:.0}%", chance * 100.).as_str(),
+                "Crit Chance",
+            ),
+            CommonUpgrade::CritDamage(multiplier) => UpgradeDescription::new_with_line2(
+                "upgrade_crit",
+                format!("+ {:.0}%", multiplier * 100.).as_str(),
+                "Crit Damage",
+            ),
         }
     }
 }
@@ -104,14 +150,66 @@ pub enum WeaponUpgrade {
     Launcher(LauncherUpgrade),
     Balls(BallsUpgrade),
     Dash(DashUpgrade),
+    Beam(BeamUpgrade),
+    Melee(MeleeUpgrade),
 }
 
 impl WeaponUpgrade {
     pub fn description(&self) -> UpgradeDescription {
-        match self {
+        let mut description = match self {
             WeaponUpgrade::Launcher(upgrade) => upgrade.description(),
             WeaponUpgrade::Balls(upgrade) => upgrade.description(),
             WeaponUpgrade::Dash(upgrade) => upgrade.description(),
+            WeaponUpgrade::Beam(upgrade) => upgrade.description(),
+            WeaponUpgrade::Melee(upgrade) => upgrade.description(),
+        };
+        if let Some(synergy) = Synergy::for_evolution(self) {
+            description.text.push(synergy.label.to_string());
+        }
+        description
+    }
+
+    /// Same variant, ignoring the values inside.
+    pub fn same_kind(&self, other: &WeaponUpgrade) -> bool {
+        match (self, other) {
+            (WeaponUpgrade::Launcher(a), WeaponUpgrade::Launcher(b)) => {
+                discriminant(a) == discriminant(b)
+            }
+            (WeaponUpgrade::Balls(a), WeaponUpgrade::Balls(b)) => {
+                discriminant(a) == discriminant(b)
+            }
+            (WeaponUpgrade::Dash(a), WeaponUpgrade::Dash(b)) => discriminant(a) == discriminant(b),
+            (WeaponUpgrade::Beam(a), WeaponUpgrade::Beam(b)) => discriminant(a) == discriminant(b),
+            (WeaponUpgrade::Melee(a), WeaponUpgrade::Melee(b)) => {
+                discriminant(a) == discriminant(b)
+            }
+            _ => false,
+        }
+    }
+
+    /// Evolutions are only offered through synergies, never from the regular pool.
+    pub fn is_evolution(&self) -> bool {
+        matches!(
+            self,
+            WeaponUpgrade::Launcher(LauncherUpgrade::Barrage)
+                | WeaponUpgrade::Balls(BallsUpgrade::Nova)
+                | WeaponUpgrade::Dash(DashUpgrade::Comet)
+                | WeaponUpgrade::Beam(BeamUpgrade::Inferno)
+                | WeaponUpgrade::Melee(MeleeUpgrade::Quake)
+        )
+    }
+
+    /// Special upgrades change how a weapon works and aren't offered twice in a row.
+    pub fn is_special(&self) -> bool {
+        match self {
+            WeaponUpgrade::Launcher(LauncherUpgrade::DoubleBullet)
+            | WeaponUpgrade::Launcher(LauncherUpgrade::Homing)
+            | WeaponUpgrade::Launcher(LauncherUpgrade::Fragment)
+            | WeaponUpgrade::Balls(BallsUpgrade::Split)
+            | WeaponUpgrade::Dash(DashUpgrade::Bullets)
+            | WeaponUpgrade::Beam(BeamUpgrade::Ignite)
+            | WeaponUpgrade::Melee(MeleeUpgrade::Shockwave) => true,
+            _ => false,
         }
     }
 }
@@ -121,6 +219,13 @@ pub enum LauncherUpgrade {
     FireRate(f32),
     Damage(f32),
     DoubleBullet,
+    Burn(f32),
+    Poison(f32),
+    Pierce(u32),
+    Ricochet(u32),
+    Homing,
+    Fragment,
+    Barrage,
 }
 
 #[derive(Clone)]
@@ -129,6 +234,8 @@ pub enum BallsUpgrade {
     Damage(f32),
     RotateSpeed(f32),
     Split,
+    Frost(f32),
+    Nova,
 }
 
 #[derive(Clone)]
@@ -136,9 +243,50 @@ pub enum DashUpgrade {
     Damage(f32),
     TimerDecrease(f32),
     Bullets,
+    Stun(f32),
+    Comet,
+}
+
+#[derive(Clone)]
+pub enum BeamUpgrade {
+    Damage(f32),
+    Range(f32),
+    TurnSpeed(f32),
+    Cooling(f32),
+    Ignite,
+    Inferno,
+}
+
+#[derive(Clone)]
+pub enum MeleeUpgrade {
+    Damage(f32),
+    Range(f32),
+    SwingSpeed(f32),
+    Shockwave,
+    Stun(f32),
+    Quake,
 }
 
 impl LauncherUpgrade {
+    pub fn modifiers(&self) -> Vec<StatModifier> {
+        match self {
+            LauncherUpgrade::FireRate(rate) => vec![StatModifier::percent(Stat::Cooldown, -rate)],
+            LauncherUpgrade::Damage(dmg) => vec![StatModifier::flat(Stat::Damage, *dmg)],
+            LauncherUpgrade::Burn(dps) => vec![StatModifier::flat(Stat::Burn, *dps)],
+            LauncherUpgrade::Poison(dps) => vec![StatModifier::flat(Stat::Poison, *dps)],
+            LauncherUpgrade::Pierce(amount) => {
+                vec![StatModifier::flat(Stat::Pierce, *amount as f32)]
+            }
+            LauncherUpgrade::Ricochet(amount) => {
+                vec![StatModifier::flat(Stat::Bounces, *amount as f32)]
+            }
+            LauncherUpgrade::DoubleBullet
+            | LauncherUpgrade::Homing
+            | LauncherUpgrade::Fragment
+            | LauncherUpgrade::Barrage => vec![],
+        }
+    }
+
     pub fn description(&self) -> UpgradeDescription {
         match self {
             LauncherUpgrade::FireRate(rate) => UpgradeDescription::new_with_line2(
@@ -154,11 +302,47 @@ impl LauncherUpgrade {
             LauncherUpgrade::DoubleBullet => {
                 UpgradeDescription::new_with_line2("upgrade_launcher", "Double", "Bullets")
             }
+            LauncherUpgrade::Burn(_) => {
+                UpgradeDescription::new_with_line2("upgrade_launcher", "Burning", "Bullets")
+            }
+            LauncherUpgrade::Poison(_) => {
+                UpgradeDescription::new_with_line2("upgrade_launcher", "Poison", "Bullets")
+            }
+            LauncherUpgrade::Pierce(amount) => UpgradeDescription::new_with_line2(
+                "upgrade_launcher",
+                format!("+ {}", amount).as_str(),
+                "Pierce",
+            ),
+            LauncherUpgrade::Ricochet(amount) => UpgradeDescription::new_with_line2(
+                "upgrade_launcher",
+                format!("+ {}", amount).as_str(),
+                "Ricochet",
+            ),
+            LauncherUpgrade::Homing => {
+                UpgradeDescription::new_with_line2("upgrade_launcher", "Homing", "Bullets")
+            }
+            LauncherUpgrade::Fragment => {
+                UpgradeDescription::new_with_line2("upgrade_launcher", "Fragment", "on Impact")
+            }
+            LauncherUpgrade::Barrage => UpgradeDescription::new("upgrade_launcher", "Barrage"),
         }
     }
 }
 
 impl BallsUpgrade {
+    pub fn modifiers(&self) -> Vec<StatModifier> {
+        match self {
+            BallsUpgrade::Amount(amount) => vec![StatModifier::flat(Stat::Amount, *amount as f32)],
+            BallsUpgrade::Damage(dmg) => vec![StatModifier::flat(Stat::Damage, *dmg)],
+            BallsUpgrade::RotateSpeed(speed) => {
+                vec![StatModifier::percent(Stat::RotationSpeed, *speed)]
+            }
+            BallsUpgrade::Split => vec![],
+            BallsUpgrade::Frost(slow) => vec![StatModifier::flat(Stat::Slow, *slow)],
+            BallsUpgrade::Nova => vec![StatModifier::flat(Stat::Amount, 1.)],
+        }
+    }
+
     pub fn description(&self) -> UpgradeDescription {
         match self {
             BallsUpgrade::Amount(amount) => UpgradeDescription::new_with_line2(
@@ -179,11 +363,27 @@ impl BallsUpgrade {
             BallsUpgrade::Split => {
                 UpgradeDescription::new_with_line2("upgrade_balls", "Bullets", "on Hit")
             }
+            BallsUpgrade::Frost(_) => {
+                UpgradeDescription::new_with_line2("upgrade_balls", "Frost", "Balls")
+            }
+            BallsUpgrade::Nova => UpgradeDescription::new("upgrade_balls", "Nova"),
         }
     }
 }
 
 impl DashUpgrade {
+    pub fn modifiers(&self) -> Vec<StatModifier> {
+        match self {
+            DashUpgrade::Damage(increase) => vec![StatModifier::flat(Stat::Damage, *increase)],
+            DashUpgrade::TimerDecrease(decrease) => {
+                vec![StatModifier::percent(Stat::Cooldown, -decrease)]
+            }
+            DashUpgrade::Bullets => vec![],
+            DashUpgrade::Stun(duration) => vec![StatModifier::flat(Stat::Stun, *duration)],
+            DashUpgrade::Comet => vec![StatModifier::percent(Stat::Damage, 0.5)],
+        }
+    }
+
     pub fn description(&self) -> UpgradeDescription {
         // TODO
 
@@ -201,17 +401,112 @@ impl DashUpgrade {
             DashUpgrade::Bullets => {
                 UpgradeDescription::new_with_line2("upgrade_dash", "Bullets", "on Hit")
             }
+            DashUpgrade::Stun(_) => {
+                UpgradeDescription::new_with_line2("upgrade_dash", "Stun", "on Hit")
+            }
+            DashUpgrade::Comet => UpgradeDescription::new("upgrade_dash", "Comet"),
         }
     }
 }
 
+impl BeamUpgrade {
+    pub fn modifiers(&self) -> Vec<StatModifier> {
+        match self {
+            BeamUpgrade::Damage(increase) => vec![StatModifier::flat(Stat::Damage, *increase)],
+            BeamUpgrade::Range(increase) => vec![StatModifier::flat(Stat::Range, *increase)],
+            BeamUpgrade::TurnSpeed(speed) => {
+                vec![StatModifier::percent(Stat::RotationSpeed, *speed)]
+            }
+            BeamUpgrade::Cooling(cooling) => vec![StatModifier::percent(Stat::Cooling, *cooling)],
+            BeamUpgrade::Ignite => vec![StatModifier::flat(Stat::Burn, 6.)],
+            BeamUpgrade::Inferno => vec![
+                StatModifier::flat(Stat::Burn, 12.),
+                StatModifier::percent(Stat::Damage, 0.5),
+            ],
+        }
+    }
+
+    pub fn description(&self) -> UpgradeDescription {
+        match self {
+            BeamUpgrade::Damage(dps) => UpgradeDescription::new_with_line2(
+                "upgrade_beam",
+                format!("+ {:.0}", dps).as_str(),
+                "Damage/s",
+            ),
+            BeamUpgrade::Range(range) => UpgradeDescription::new_with_line2(
+                "upgrade_beam",
+                format!("+ {:.0}", range).as_str(),
+                "Range",
+            ),
+            BeamUpgrade::TurnSpeed(speed) => UpgradeDescription::new_with_line2(
+                "upgrade_beam",
+                format!("+ {:.0}%", speed * 100.).as_str(),
+                "Sweep Speed",
+            ),
+            BeamUpgrade::Cooling(cooling) => UpgradeDescription::new_with_line2(
+                "upgrade_beam",
+                format!("+ {:.0}%", cooling * 100.).as_str(),
+                "Cooling",
+            ),
+            BeamUpgrade::Ignite => {
+                UpgradeDescription::new_with_line2("upgrade_beam", "Burning", "Beam")
+            }
+            BeamUpgrade::Inferno => UpgradeDescription::new("upgrade_beam", "Inferno"),
+        }
+    }
+}
+
+impl MeleeUpgrade {
+    pub fn modifiers(&self) -> Vec<StatModifier> {
+        match self {
+            MeleeUpgrade::Damage(increase) => vec![StatModifier::flat(Stat::Damage, *increase)],
+            MeleeUpgrade::Range(increase) => vec![StatModifier::flat(Stat::Range, *increase)],
+            MeleeUpgrade::SwingSpeed(speed) => vec![StatModifier::percent(Stat::Cooldown, -speed)],
+            MeleeUpgrade::Shockwave => vec![],
+            MeleeUpgrade::Stun(duration) => vec![StatModifier::flat(Stat::Stun, *duration)],
+            MeleeUpgrade::Quake => vec![],
+        }
+    }
+
+    pub fn description(&self) -> UpgradeDescription {
+        match self {
+            MeleeUpgrade::Damage(dmg) => UpgradeDescription::new_with_line2(
+                "upgrade_melee",
+                format!("+ {:.0}", dmg).as_str(),
+                "Damage",
+            ),
+            MeleeUpgrade::Range(range) => UpgradeDescription::new_with_line2(
+                "upgrade_melee",
+                format!("+ {:.0}", range).as_str(),
+                "Reach",
+            ),
+            MeleeUpgrade::SwingSpeed(speed) => UpgradeDescription::new_with_line2(
+                "upgrade_melee",
+                format!("+ {:.0}%", speed * 100.).as_str(),
+                "Swing Speed",
+            ),
+            MeleeUpgrade::Shockwave => {
+                UpgradeDescription::new_with_line2("upgrade_melee", "Finisher", "Shockwave")
+            }
+            MeleeUpgrade::Stun(_) => {
+                UpgradeDescription::new_with_line2("upgrade_melee", "Stun", "on Hit")
+            }
+            MeleeUpgrade::Quake => UpgradeDescription::new("upgrade_melee", "Quake"),
+        }
+    }
+}
+
+const REROLLS_PER_RUN: u32 = 3;
+const BANISHES_PER_RUN: u32 = 3;
+
 pub struct Upgrades {
     item_upgrades: Vec<ItemUpgrade>,
     common_upgrades: Vec<CommonUpgrade>,
-    launcher_upgrades: Vec<LauncherUpgrade>,
-    balls_upgrades: Vec<BallsUpgrade>,
-    dash_upgrades: Vec<DashUpgrade>,
     special_weapon_used: bool,
+    /// Upgrades removed from the offers for the rest of the run.
+    banished: Vec<Upgrade>,
+    pub rerolls: u32,
+    pub banishes: u32,
 }
 
 impl Upgrades {
@@ -226,118 +521,193 @@ impl Upgrades {
                 CommonUpgrade::MaxHp(2),
                 CommonUpgrade::MoveSpeed(0.1),
                 CommonUpgrade::ItemDropChance(3),
-            ],
-            launcher_upgrades: vec![
-                LauncherUpgrade::FireRate(0.05),
-                LauncherUpgrade::Damage(3.),
-                LauncherUpgrade::DoubleBullet,
-            ],
-            balls_upgrades: vec![
-                BallsUpgrade::Amount(2),
-                BallsUpgrade::Damage(18.),
-                BallsUpgrade::RotateSpeed(0.2),
-                BallsUpgrade::Split,
-            ],
-            dash_upgrades: vec![
-                DashUpgrade::Damage(30.),
-                DashUpgrade::TimerDecrease(0.15),
-                DashUpgrade::Bullets,
+                CommonUpgrade::CritChance(0.05),
+                CommonUpgrade::CritDamage(0.25),
             ],
             special_weapon_used: false,
+            banished: vec![],
+            rerolls: REROLLS_PER_RUN,
+            banishes: BANISHES_PER_RUN,
+        }
+    }
+
+    pub fn is_banished(&self, upgrade: &Upgrade) -> bool {
+        self.banished
+            .iter()
+            .any(|banished| banished.same_kind(upgrade))
+    }
+
+    /// The weapon's pool without banished upgrades. Falls back to the full pool if all are banished.
+    fn weapon_pool(&self, weapon: &dyn WeaponBehavior) -> Vec<WeaponUpgrade> {
+        let pool = weapon
+            .upgrade_pool()
+            .into_iter()
+            .filter(|upgrade| !self.is_banished(&Upgrade::WeaponUpgrade(upgrade.clone())))
+            .collect::<Vec<_>>();
+        if pool.is_empty() {
+            weapon.upgrade_pool()
+        } else {
+            pool
+        }
+    }
+
+    fn item_pool(&self) -> Vec<ItemUpgrade> {
+        let pool = self
+            .item_upgrades
+            .iter()
+            .filter(|upgrade| !self.is_banished(&Upgrade::Item((*upgrade).clone())))
+            .cloned()
+            .collect::<Vec<_>>();
+        if pool.is_empty() {
+            self.item_upgrades.clone()
+        } else {
+            pool
+        }
+    }
+
+    fn common_pool(&self) -> Vec<CommonUpgrade> {
+        let pool = self
+            .common_upgrades
+            .iter()
+            .filter(|upgrade| !self.is_banished(&Upgrade::CommonUpgrade((*upgrade).clone())))
+            .cloned()
+            .collect::<Vec<_>>();
+        if pool.is_empty() {
+            self.common_upgrades.clone()
+        } else {
+            pool
         }
     }
 
-    pub fn get_weapon_upgrade(&self, weapon: &Weapon) -> (Upgrade, bool) {
-        let weapon_upgrade = match weapon {
-            Weapon::Launcher(_) => Upgrade::WeaponUpgrade(WeaponUpgrade::Launcher(
-                self.launcher_upgrades[rand::gen_range(0, self.launcher_upgrades.len())].clone(),
-            )),
-            Weapon::Balls(_) => Upgrade::WeaponUpgrade(WeaponUpgrade::Balls(
-                self.balls_upgrades[rand::gen_range(0, self.balls_upgrades.len())].clone(),
-            )),
-            Weapon::Dash(_) => Upgrade::WeaponUpgrade(WeaponUpgrade::Dash(
-                self.dash_upgrades[rand::gen_range(0, self.dash_upgrades.len())].clone(),
-            )),
+    fn get_common_upgrade(&self) -> Upgrade {
+        let pool = self.common_pool();
+        Upgrade::CommonUpgrade(pool[rand::gen_range(0, pool.len())].clone())
+    }
+
+    pub fn get_weapon_upgrade(&self, weapon: &dyn WeaponBehavior) -> (Upgrade, bool) {
+        let pool = self.weapon_pool(weapon);
+        let regular = pool
+            .iter()
+            .filter(|upgrade| !upgrade.is_special())
+            .cloned()
+            .collect::<Vec<_>>();
+        let pool = if self.special_weapon_used && !regular.is_empty() {
+            regular
+        } else {
+            pool
         };
+        let upgrade = pool[rand::gen_range(0, pool.len())].clone();
 
-        let is_special_upgrade = match &weapon_upgrade {
-            Upgrade::WeaponUpgrade(up) => match up {
-                WeaponUpgrade::Launcher(up) => match up {
-                    LauncherUpgrade::DoubleBullet => true,
-                    _ => false,
-                },
-                WeaponUpgrade::Balls(up) => match up {
-                    BallsUpgrade::Split => true,
-                    _ => false,
-                },
-                WeaponUpgrade::Dash(up) => match up {
-                    DashUpgrade::Bullets => true,
+        let is_special_upgrade = upgrade.is_special();
+        (Upgrade::WeaponUpgrade(upgrade), is_special_upgrade)
+    }
+
+    /// An item or common upgrade for the slots after the weapon upgrade. None means roll again.
+    fn get_extra_upgrade(
+        &self,
+        weapon: &dyn WeaponBehavior,
+        missing_hp: f32,
+        aberration: f32,
+    ) -> Option<Upgrade> {
+        let collection_index = rand::gen_range(0, 2);
+        match collection_index {
+            0 => {
+                let pool = self.item_pool();
+                let upgrade = pool[rand::gen_range(0, pool.len())].clone();
+                if let ItemUpgrade::Hp(hp) = upgrade {
+                    if hp > missing_hp {
+                        return None;
+                    }
+                }
+                if match upgrade {
+                    ItemUpgrade::AnomalyBig | ItemUpgrade::AnomalySmall => aberration >= 1.,
                     _ => false,
+                } {
+                    let pool = self.weapon_pool(weapon);
+                    return Some(Upgrade::WeaponUpgrade(
+                        pool[rand::gen_range(0, pool.len())].clone(),
+                    ));
+                }
+
+                Some(Upgrade::Item(upgrade))
+            }
+            1 => Some(self.get_common_upgrade()),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn reroll(
+        &mut self,
+        weapon: &dyn WeaponBehavior,
+        missing_hp: f32,
+        aberration: f32,
+    ) -> Vec<Upgrade> {
+        self.rerolls = self.rerolls.saturating_sub(1);
+        self.generate_upgrades(weapon, missing_hp, aberration)
+    }
+
+    /// Banishes the offered upgrade at `index` and replaces it with one of the same category.
+    pub fn banish(
+        &mut self,
+        offered: &mut [Upgrade],
+        index: usize,
+        weapon: &dyn WeaponBehavior,
+        missing_hp: f32,
+        aberration: f32,
+    ) {
+        self.banishes = self.banishes.saturating_sub(1);
+        self.banished.push(offered[index].clone());
+
+        // Avoid showing the same upgrade twice, but give up eventually if the pools are small
+        for _ in 0..20 {
+            let replacement = match offered[index] {
+                Upgrade::WeaponUpgrade(_) => self.get_weapon_upgrade(weapon).0,
+                _ => match self.get_extra_upgrade(weapon, missing_hp, aberration) {
+                    Some(upgrade) => upgrade,
+                    None => continue,
                 },
-            },
-            _ => false,
-        };
-        if is_special_upgrade && self.special_weapon_used {
-            return self.get_weapon_upgrade(weapon);
+            };
+            let duplicate = offered
+                .iter()
+                .any(|upgrade| upgrade.same_kind(&replacement));
+            offered[index] = replacement;
+            if !duplicate && !self.is_banished(&offered[index]) {
+                break;
+            }
         }
-        (weapon_upgrade, is_special_upgrade)
     }
 
     pub fn generate_upgrades(
         &mut self,
-        weapon: &Weapon,
+        weapon: &dyn WeaponBehavior,
         missing_hp: f32,
         aberration: f32,
     ) -> Vec<Upgrade> {
+        let evolutions = unlocked_evolutions(&weapon.taken_upgrades())
+            .into_iter()
+            .filter(|evolution| !self.is_banished(&Upgrade::WeaponUpgrade(evolution.clone())))
+            .collect::<Vec<_>>();
         let (weapon_upgrade, is_special_upgrade) = self.get_weapon_upgrade(weapon);
         self.special_weapon_used = is_special_upgrade;
 
         let mut upgrades = vec![weapon_upgrade];
-        while upgrades.len() < 3 {
-            let collection_index = rand::gen_range(0, 2);
-            match collection_index {
-                0 => {
-                    let upgrade =
-                        self.item_upgrades[rand::gen_range(0, self.item_upgrades.len())].clone();
-                    if let ItemUpgrade::Hp(hp) = upgrade {
-                        if hp > missing_hp {
-                            continue;
-                        }
-                    }
-                    if match upgrade {
-                        ItemUpgrade::AnomalyBig | ItemUpgrade::AnomalySmall => aberration >= 1.,
-                        _ => false,
-                    } {
-                        let weapon_upgrade = match weapon {
-                            Weapon::Launcher(_) => Upgrade::WeaponUpgrade(WeaponUpgrade::Launcher(
-                                self.launcher_upgrades
-                                    [rand::gen_range(0, self.launcher_upgrades.len())]
-                                .clone(),
-                            )),
-                            Weapon::Balls(_) => Upgrade::WeaponUpgrade(WeaponUpgrade::Balls(
-                                self.balls_upgrades[rand::gen_range(0, self.balls_upgrades.len())]
-                                    .clone(),
-                            )),
-                            Weapon::Dash(_) => Upgrade::WeaponUpgrade(WeaponUpgrade::Dash(
-                                self.dash_upgrades[rand::gen_range(0, self.dash_upgrades.len())]
-                                    .clone(),
-                            )),
-                        };
-                        upgrades.push(weapon_upgrade);
-                        continue;
-                    }
-
-                    upgrades.push(Upgrade::Item(upgrade));
-                }
-                1 => {
-                    let upgrade = self.common_upgrades
-                        [rand::gen_range(0, self.common_upgrades.len())]
-                    .clone();
-                    upgrades.push(Upgrade::CommonUpgrade(upgrade));
-                }
-                _ => unreachable!(),
+        // An unlocked evolution takes one of the extra slots so the regular weapon upgrade stays on offer
+        if !evolutions.is_empty() {
+            let evolution = evolutions[rand::gen_range(0, evolutions.len())].clone();
+            upgrades.push(Upgrade::WeaponUpgrade(evolution));
+        }
+        // Item upgrades can be rejected, so only retry a few times before settling for common ones
+        for _ in 0..20 {
+            if upgrades.len() == 3 {
+                break;
+            }
+            if let Some(upgrade) = self.get_extra_upgrade(weapon, missing_hp, aberration) {
+                upgrades.push(upgrade);
             }
         }
+        while upgrades.len() < 3 {
+            upgrades.push(self.get_common_upgrade());
+        }
 
         upgrades
         // vec![
@@ -355,11 +725,13 @@ impl Upgrades {
         // ]
     }
 
+    /// Three random weapons to pick from at the start of a run.
     pub fn weapon_selection() -> Vec<Upgrade> {
-        vec![
-            Upgrade::Weapon(WeaponType::Launcher),
-            Upgrade::Weapon(WeaponType::Balls),
-            Upgrade::Weapon(WeaponType::Dash),
-        ]
+        let mut pool = WeaponType::ALL.to_vec();
+        let mut selection = vec![];
+        while selection.len() < 3 && pool.len() > 0 {
+            selection.push(Upgrade::Weapon(pool.remove(rand::gen_range(0, pool.len()))));
+        }
+        selection
     }
 }
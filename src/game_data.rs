@@ -3,14 +3,22 @@ use std::collections::HashMap;
 use macroquad::{audio::Sound, prelude::*};
 
 use crate::{
+    bullet_pattern::BulletPatterns,
+    difficulty::DifficultyProfile,
     entity::{
-        entities::Ecs, entity_id::Entity, mirituhg::spawn_mirituhg, player::spawn_player,
-        spawner::spawn_spawner, upgrades::Upgrades,
+        damage::DamageDealt,
+        entities::Ecs,
+        entity_id::Entity,
+        mirituhg::spawn_mirituhg,
+        pickup::{spawn_pickup, Pickup},
+        player::spawn_player,
+        spawner::spawn_spawner,
+        upgrades::Upgrades,
     },
     game_state::GameState,
     input_manager::InputManager,
-    items::weapon::{Launcher, Weapon},
-    map::map::Map,
+    items::weapon::{Launcher, WeaponBehavior, WeaponType},
+    map::{map::Map, navigation::NavGrid},
     rand_utils::rand_dir,
     room::Room,
     settings::GameSettings,
@@ -21,6 +29,8 @@ use crate::{
     },
 };
 
+const WEAPON_PICKUP_CHANCE: f32 = 0.15;
+
 pub enum GameMaterial {
     Aberration(Material),
     Color(Material),
@@ -85,7 +95,9 @@ pub struct GameData {
     pub camera: Camera2D,
     pub debug_collisions: bool,
     pub show_fps: bool,
-    pub weapon: Weapon,
+    pub show_stats: bool,
+    pub weapon: Box<dyn WeaponBehavior>,
+    pub secondary_weapon: Option<Box<dyn WeaponBehavior>>,
     pub current_room: Room,
     pub next_room: Option<Room>,
     pub maps: Vec<Map>,
@@ -103,6 +115,14 @@ pub struct GameData {
     pub screen_shake: ScreenShake,
     pub completed_rooms: usize,
     pub upgrades: Upgrades,
+    pub navigation: NavGrid,
+    pub difficulty: DifficultyProfile,
+    pub bullet_patterns: BulletPatterns,
+    pub run_seed: u64,
+    pub boss_warning: Option<Timer>,
+    pub bosses_defeated: usize,
+    /// Damage that landed this frame, after crits and resistances.
+    pub damage_dealt: Vec<DamageDealt>,
 }
 
 impl GameData {
@@ -115,6 +135,8 @@ impl GameData {
         audio: Audio,
         death_texture: Texture2D,
         end_game_texture: Texture2D,
+        difficulty: DifficultyProfile,
+        bullet_patterns: BulletPatterns,
     ) -> Self {
         let camera = Camera2D::default();
         Self {
@@ -131,8 +153,10 @@ impl GameData {
             show_fps: true,
             #[cfg(not(debug_assertions))]
             show_fps: false,
-            weapon: Weapon::Launcher(Launcher::new()),
-            current_room: Room::new(0, 0.),
+            show_stats: false,
+            weapon: Box::new(Launcher::new()),
+            secondary_weapon: None,
+            current_room: Room::new(0, vec![], difficulty.wave_config()),
             next_room: None,
             maps,
             screen_dimmer: ScreenDimmer::new(),
@@ -149,16 +173,29 @@ impl GameData {
             screen_shake: ScreenShake::new(),
             completed_rooms: 0,
             upgrades: Upgrades::new(),
+            navigation: NavGrid::new(),
+            difficulty,
+            bullet_patterns,
+            run_seed: 0,
+            boss_warning: None,
+            bosses_defeated: 0,
+            damage_dealt: vec![],
         }
     }
 
     pub fn reset(&mut self) {
         self.state = GameState::Intro;
-        self.weapon = Weapon::Launcher(Launcher::new());
-        self.current_room = Room::new(0, 3.);
+        self.weapon = Box::new(Launcher::new());
+        self.secondary_weapon = None;
+        self.upgrades = Upgrades::new();
+        self.run_seed = rand::rand() as u64;
+        self.current_room = self.generate_room(0, 0);
         self.next_room = None;
         self.dead = false;
         self.completed_rooms = 0;
+        self.boss_warning = None;
+        self.bosses_defeated = 0;
+        self.damage_dealt.clear();
     }
 
     pub fn new_entity(&mut self) -> Entity {
@@ -173,7 +210,7 @@ impl GameData {
 
         let shake = &mut self.screen_shake;
         self.camera.target = vec2(360. / 2., 240. / 2.) + shake.camera_offset;
-        if !self.paused {
+        if !self.paused || self.boss_warning.is_some() {
             shake.timer.update();
             shake.event_timer.update();
             if shake.event_timer.progress() > 0. && shake.timer.completed() {
@@ -185,6 +222,16 @@ impl GameData {
             shake.camera_offset = Vec2::ZERO;
         }
 
+        if let Some(warning) = &mut self.boss_warning {
+            warning.update();
+            if warning.just_completed() {
+                self.boss_warning = None;
+                self.screen_dimmer.dim();
+                self.paused = true;
+                self.pause_timer.reset();
+            }
+        }
+
         for mat in &self.graphics.materials {
             match mat.1 {
                 GameMaterial::Aberration(mat) => {
@@ -208,10 +255,96 @@ impl GameData {
         &self.maps[self.current_room.map_index]
     }
 
+    pub fn current_map_mut(&mut self) -> &mut Map {
+        &mut self.maps[self.current_room.map_index]
+    }
+
+    pub fn boss_map_index(&self) -> Option<usize> {
+        self.maps.iter().position(|map| map.boss_spawn.is_some())
+    }
+
+    pub fn generate_room(&self, map_index: usize, room_index: usize) -> Room {
+        let enemies = self.difficulty.compose_encounter(room_index, self.run_seed);
+        let mut room = Room::new(map_index, enemies, self.difficulty.wave_config());
+        room.elite_chance = self.difficulty.elites.chance(room_index);
+        room.elite_affixes = self.difficulty.elites.affix_count(room_index);
+        room
+    }
+
+    pub fn holds_weapon(&self, weapon_type: &WeaponType) -> bool {
+        self.weapon.weapon_type() == *weapon_type
+            || self
+                .secondary_weapon
+                .as_ref()
+                .map_or(false, |weapon| weapon.weapon_type() == *weapon_type)
+    }
+
+    /// Swaps the primary and secondary weapon. Each keeps its own upgrades.
+    pub fn swap_weapons(&mut self, ecs: &mut Ecs, player_e: Entity) -> bool {
+        let Some(mut secondary) = self.secondary_weapon.take() else {
+            return false;
+        };
+        self.weapon.on_unequip(ecs, player_e);
+        std::mem::swap(&mut self.weapon, &mut secondary);
+        self.secondary_weapon = Some(secondary);
+        true
+    }
+
+    /// Fills the empty secondary slot.
+    pub fn pick_up_weapon(&mut self, weapon: Box<dyn WeaponBehavior>) {
+        self.secondary_weapon = Some(weapon);
+    }
+
+    /// Replaces the primary weapon and hands the old one back so it can be dropped with its upgrades.
+    pub fn replace_weapon(
+        &mut self,
+        ecs: &mut Ecs,
+        player_e: Entity,
+        weapon: Box<dyn WeaponBehavior>,
+    ) -> Box<dyn WeaponBehavior> {
+        self.weapon.on_unequip(ecs, player_e);
+        std::mem::replace(&mut self.weapon, weapon)
+    }
+
+    /// Missing hp and aberration of the player, which decide the item upgrades offered.
+    fn upgrade_conditions(ecs: &Ecs) -> (f32, f32) {
+        let players = ecs.check_components(|e, comps| {
+            comps.player_data.contains_key(e) && comps.health.contains_key(e)
+        });
+        let Some(player_e) = players.first() else {
+            return (0., 0.);
+        };
+        let player_data = ecs.components.player_data.get(player_e).unwrap();
+        let health = ecs.components.health.get(player_e).unwrap();
+        (
+            player_data.get_upgraded_data().max_hp as f32 - health.hp,
+            player_data.aberration,
+        )
+    }
+
+    pub fn reroll_upgrades(&mut self, ecs: &Ecs) {
+        let (missing_hp, aberration) = Self::upgrade_conditions(ecs);
+        self.current_room.available_upgrades =
+            self.upgrades
+                .reroll(self.weapon.as_ref(), missing_hp, aberration);
+    }
+
+    pub fn banish_upgrade(&mut self, ecs: &Ecs, index: usize) {
+        let (missing_hp, aberration) = Self::upgrade_conditions(ecs);
+        self.upgrades.banish(
+            &mut self.current_room.available_upgrades,
+            index,
+            self.weapon.as_ref(),
+            missing_hp,
+            aberration,
+        );
+    }
+
     pub fn spawn_map_entities(&mut self, ecs: &mut Ecs) -> Vec2 {
+        self.current_map_mut().reset();
+
         let mut player_pos = Vec2::ZERO;
         let mut spawner_positions = vec![];
-        let mut spawn_boss = false;
         for (_, layer) in &self.current_map().tiled_map.layers {
             for object in &layer.objects {
                 let object_pos = vec2(object.world_x + 4., object.world_y - 4.);
@@ -224,14 +357,34 @@ impl GameData {
                 if let Some(_) = object.properties.get("spawn") {
                     spawner_positions.push(object_pos);
                 }
-                if let Some(_) = object.properties.get("boss") {
-                    spawn_boss = true;
-                }
             }
         }
 
-        if spawn_boss {
-            spawn_mirituhg(self, vec2(180., 120.), ecs);
+        if self.current_room.boss {
+            if let Some(boss_pos) = self.current_map().boss_spawn {
+                spawn_mirituhg(self, boss_pos, ecs);
+                self.current_room.locked = true;
+            }
+        }
+
+        let missing_weapons = WeaponType::ALL
+            .into_iter()
+            .filter(|weapon_type| !self.holds_weapon(weapon_type))
+            .collect::<Vec<_>>();
+        if !self.current_room.boss
+            && self.completed_rooms > 0
+            && spawner_positions.len() > 0
+            && missing_weapons.len() > 0
+            && rand::gen_range(0., 1.) < WEAPON_PICKUP_CHANCE
+        {
+            let weapon_type = missing_weapons[rand::gen_range(0, missing_weapons.len())].clone();
+            let pos = spawner_positions[rand::gen_range(0, spawner_positions.len())];
+            spawn_pickup(
+                self,
+                (pos + player_pos) / 2.,
+                ecs,
+                Pickup::Weapon(weapon_type),
+            );
         }
 
         for pos in spawner_positions {
@@ -242,14 +395,21 @@ impl GameData {
     }
 
     pub fn next_room(&mut self, ecs: &mut Ecs) {
+        if self.current_room.locked || self.boss_warning.is_some() {
+            return;
+        }
         self.current_room.despawn(ecs);
 
+        let regular_maps = (0..self.maps.len())
+            .filter(|i| self.maps[*i].boss_spawn.is_none())
+            .collect::<Vec<_>>();
         let map_index = match self.completed_rooms {
-            0 => 1,
-            _ => rand::gen_range(1, self.maps.len()),
+            0 => regular_maps[0],
+            _ => regular_maps[rand::gen_range(0, regular_maps.len())],
         };
 
-        let mut new_room = Room::new(map_index, 2. + 3. * self.completed_rooms as f32);
+        let mut new_room = self.generate_room(map_index, self.completed_rooms);
+        let mut boss_incoming = false;
 
         new_room.available_upgrades = if self.completed_rooms == 0 {
             Upgrades::weapon_selection()
@@ -260,12 +420,14 @@ impl GameData {
             let health = ecs.components.health.get(&players[0]).unwrap();
 
             if player_data.aberration >= 1. {
-                // TODO: boss room
-                new_room.map_index = 0;
+                if let Some(boss_map_index) = self.boss_map_index() {
+                    new_room = Room::new_boss(boss_map_index, self.difficulty.wave_config());
+                    boss_incoming = true;
+                }
             }
 
             self.upgrades.generate_upgrades(
-                &self.weapon,
+                self.weapon.as_ref(),
                 up_data.max_hp as f32 - health.hp,
                 player_data.aberration,
             )
@@ -273,9 +435,15 @@ impl GameData {
 
         self.next_room = Some(new_room);
         self.map_change_requested = true;
-        self.screen_dimmer.dim();
+        if boss_incoming {
+            self.boss_warning = Some(Timer::new(2.5, false));
+            self.screen_shake.shake(2.5, 2.);
+        } else {
+            self.screen_dimmer.dim();
+            // The boss warning resets the pause timer once it's done
+            self.pause_timer.reset();
+        }
         self.paused = true;
-        self.pause_timer.reset();
         self.completed_rooms += 1;
     }
 }
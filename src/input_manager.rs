@@ -2,7 +2,9 @@ use gamepads::{Button, Gamepads};
 use macroquad::{
     camera::Camera2D,
     input::{
-        is_key_down, is_key_pressed, is_key_released, mouse_delta_position, mouse_position, KeyCode,
+        is_key_down, is_key_pressed, is_key_released, is_mouse_button_down,
+        is_mouse_button_pressed, is_mouse_button_released, mouse_delta_position, mouse_position,
+        KeyCode, MouseButton,
     },
     math::{vec2, Vec2},
     window::screen_height,
@@ -23,6 +25,10 @@ pub enum Action {
     Down,
     Confirm,
     Pause,
+    Swap,
+    Fire,
+    /// Charges the Launcher's shot like holding Fire, but never fires regular shots.
+    AltFire,
 }
 
 impl InputManager {
@@ -191,6 +197,21 @@ impl InputManager {
                     return true;
                 }
             }
+            Action::Swap => {
+                if is_key_pressed(KeyCode::Q) || is_key_pressed(KeyCode::Tab) {
+                    return true;
+                }
+            }
+            Action::Fire => {
+                if is_mouse_button_pressed(MouseButton::Left) || is_key_pressed(KeyCode::J) {
+                    return true;
+                }
+            }
+            Action::AltFire => {
+                if is_mouse_button_pressed(MouseButton::Right) || is_key_pressed(KeyCode::K) {
+                    return true;
+                }
+            }
         }
 
         if let Some(gamepad) = gamepad {
@@ -225,6 +246,21 @@ impl InputManager {
                         return true;
                     }
                 }
+                Action::Swap => {
+                    if gamepad.is_just_pressed(Button::ActionLeft) {
+                        return true;
+                    }
+                }
+                Action::Fire => {
+                    if gamepad.is_just_pressed(Button::FrontRightLower) {
+                        return true;
+                    }
+                }
+                Action::AltFire => {
+                    if gamepad.is_just_pressed(Button::FrontLeftLower) {
+                        return true;
+                    }
+                }
             }
         }
 
@@ -267,6 +303,21 @@ impl InputManager {
                     return true;
                 }
             }
+            Action::Swap => {
+                if is_key_down(KeyCode::Q) || is_key_down(KeyCode::Tab) {
+                    return true;
+                }
+            }
+            Action::Fire => {
+                if is_mouse_button_down(MouseButton::Left) || is_key_down(KeyCode::J) {
+                    return true;
+                }
+            }
+            Action::AltFire => {
+                if is_mouse_button_down(MouseButton::Right) || is_key_down(KeyCode::K) {
+                    return true;
+                }
+            }
         }
 
         if let Some(gamepad) = gamepad {
@@ -301,6 +352,21 @@ impl InputManager {
                         return true;
                     }
                 }
+                Action::Swap => {
+                    if gamepad.is_currently_pressed(Button::ActionLeft) {
+                        return true;
+                    }
+                }
+                Action::Fire => {
+                    if gamepad.is_currently_pressed(Button::FrontRightLower) {
+                        return true;
+                    }
+                }
+                Action::AltFire => {
+                    if gamepad.is_currently_pressed(Button::FrontLeftLower) {
+                        return true;
+                    }
+                }
             }
         }
 
@@ -340,6 +406,21 @@ impl InputManager {
                     return true;
                 }
             }
+            Action::Swap => {
+                if is_key_released(KeyCode::Q) || is_key_released(KeyCode::Tab) {
+                    return true;
+                }
+            }
+            Action::Fire => {
+                if is_mouse_button_released(MouseButton::Left) || is_key_released(KeyCode::J) {
+                    return true;
+                }
+            }
+            Action::AltFire => {
+                if is_mouse_button_released(MouseButton::Right) || is_key_released(KeyCode::K) {
+                    return true;
+                }
+            }
         }
 
         if let Some(gamepad) = gamepad {
@@ -374,6 +455,21 @@ impl InputManager {
                         return true;
                     }
                 }
+                Action::Swap => {
+                    if gamepad.is_just_released(Button::ActionLeft) {
+                        return true;
+                    }
+                }
+                Action::Fire => {
+                    if gamepad.is_just_released(Button::FrontRightLower) {
+                        return true;
+                    }
+                }
+                Action::AltFire => {
+                    if gamepad.is_just_released(Button::FrontLeftLower) {
+                        return true;
+                    }
+                }
             }
         }
 
@@ -1,29 +1,201 @@
 use std::f32::consts::TAU;
 
-use macroquad::{math::Vec2, time::get_frame_time};
+use macroquad::{
+    color::Color,
+    math::{vec2, Vec2},
+    shapes::{draw_circle, draw_line, draw_triangle},
+    time::get_frame_time,
+};
 
 use crate::{
-    entity::upgrades::{BallsUpgrade, DashUpgrade, LauncherUpgrade},
+    entity::{
+        damage::{Damage, DamageType},
+        entities::Ecs,
+        entity_id::Entity,
+        events::DamageEvent,
+        projectile::{Homing, ProjectileModifiers, SplitOnImpact},
+        status::{StatusEffect, StatusKind},
+        tags::{DamageOnCollision, DespawnOnHit, EntityType},
+        upgrades::{
+            BallsUpgrade, BeamUpgrade, DashUpgrade, LauncherUpgrade, MeleeUpgrade, WeaponUpgrade,
+        },
+    },
+    input_manager::{Action, InputManager},
+    map::map::Map,
+    settings::FireMode,
+    stats::{Stat, Stats},
+    systems::collision::ColliderType,
     timer::Timer,
 };
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum WeaponType {
     Launcher,
     Balls,
     Dash,
+    Beam,
+    Melee,
+}
+
+impl WeaponType {
+    pub const ALL: [WeaponType; 5] = [
+        WeaponType::Launcher,
+        WeaponType::Balls,
+        WeaponType::Dash,
+        WeaponType::Beam,
+        WeaponType::Melee,
+    ];
+
+    pub fn create(&self) -> Box<dyn WeaponBehavior> {
+        match self {
+            WeaponType::Launcher => Box::new(Launcher::new()),
+            WeaponType::Balls => Box::new(Balls::new()),
+            WeaponType::Dash => Box::new(Dash::new()),
+            WeaponType::Beam => Box::new(Beam::new()),
+            WeaponType::Melee => Box::new(Melee::new()),
+        }
+    }
+}
+
+pub struct Shot {
+    pub damage: f32,
+    pub position: Vec2,
+    pub velocity: Vec2,
+    /// Orbiting balls keep their slot index and ignore map collision.
+    pub ball_index: Option<usize>,
+    pub modifiers: ProjectileModifiers,
+    pub scale: f32,
+}
+
+impl Shot {
+    pub fn new(damage: f32, position: Vec2, velocity: Vec2) -> Self {
+        Self {
+            damage,
+            position,
+            velocity,
+            ball_index: None,
+            modifiers: ProjectileModifiers::default(),
+            scale: 1.,
+        }
+    }
+
+    pub fn with_modifiers(mut self, modifiers: ProjectileModifiers) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    pub fn scaled(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+}
+
+/// Bullets bursting out of a hit enemy, evenly spread starting to the right.
+pub fn split_shots(position: Vec2, count: usize) -> Vec<Shot> {
+    (0..count)
+        .map(|i| Vec2::from_angle(i as f32 / count as f32 * TAU))
+        .map(|dir| Shot::new(7., position + dir * 12., dir * 50.))
+        .collect()
+}
+
+pub struct WeaponStats {
+    pub damage: f32,
+    pub cooldown: f32,
+    pub statuses: Vec<StatusEffect>,
+}
+
+pub struct WeaponContext<'a> {
+    pub input: &'a InputManager,
+    pub player_e: Entity,
+    pub player_pos: Vec2,
+    pub aim_dir: Vec2,
+    pub fire_mode: FireMode,
+    pub map: &'a Map,
 }
 
-pub enum Weapon {
-    Launcher(Launcher),
-    Balls(Balls),
-    Dash(Dash),
+impl WeaponContext<'_> {
+    /// Whether the weapon should fire in the current fire mode. `latched` is the toggle state.
+    pub fn trigger_held(&self, latched: bool) -> bool {
+        match self.fire_mode {
+            FireMode::Auto => true,
+            FireMode::Hold => self.input.is_currently_pressed(Action::Fire),
+            FireMode::Toggle => latched,
+        }
+    }
 }
 
+pub trait WeaponBehavior {
+    fn weapon_type(&self) -> WeaponType;
+
+    /// Texture drawn for the weapon in the HUD.
+    fn hud_icon(&self) -> &'static str;
+
+    fn stats(&self) -> WeaponStats;
+
+    /// Stats with the modifiers from every upgrade taken.
+    fn stat_sheet(&self) -> &Stats;
+
+    /// Advances timers and any entities the weapon owns.
+    fn update(&mut self, ctx: &WeaponContext, ecs: &mut Ecs);
+
+    /// Fires if the weapon is ready and returns the shots to spawn.
+    fn on_fire(&mut self, ctx: &WeaponContext, ecs: &mut Ecs) -> Vec<Shot>;
+
+    /// Hits the weapon deals directly this frame instead of through a projectile.
+    fn strike(&mut self, _ctx: &WeaponContext, _ecs: &mut Ecs) -> Vec<DamageEvent> {
+        vec![]
+    }
+
+    /// Called when something the weapon spawned damages an enemy.
+    fn on_hit(&mut self, _position: Vec2, _collider: &ColliderType) -> Vec<Shot> {
+        vec![]
+    }
+
+    fn on_kill(&mut self, _position: Vec2) {}
+
+    /// Draws effects that aren't entities, in world space.
+    fn draw(&self) {}
+
+    /// Progress of a charged attack that is being built up, from 0 to 1.
+    fn charge(&self) -> Option<f32> {
+        None
+    }
+
+    /// Heat from 0 to 1 and whether the weapon is overheated.
+    fn heat(&self) -> Option<(f32, bool)> {
+        None
+    }
+
+    /// Cleans up weapon owned entities and components before another weapon is equipped.
+    fn on_unequip(&mut self, _ecs: &mut Ecs, _player_e: Entity) {}
+
+    /// The player can't be hurt and floats over pits while this is true.
+    fn evading(&self) -> bool {
+        false
+    }
+
+    fn movement_override(&self) -> Option<Vec2> {
+        None
+    }
+
+    fn upgrade_pool(&self) -> Vec<WeaponUpgrade>;
+
+    /// Every upgrade applied so far, used to check synergies.
+    fn taken_upgrades(&self) -> Vec<WeaponUpgrade>;
+
+    fn apply_upgrade(&mut self, upgrade: &WeaponUpgrade);
+}
+
+const LAUNCHER_CHARGE_TIME: f32 = 1.2;
+const LAUNCHER_MIN_CHARGE: f32 = 0.25;
+const LAUNCHER_CHARGE_PIERCE: u32 = 8;
+
 pub struct Launcher {
     pub shoot_timer: Timer,
-    pub base_timer_duration: f32,
-    pub base_damage: f32,
+    pub stats: Stats,
+    /// Whether the trigger is latched in toggle fire mode.
+    pub firing: bool,
+    pub charge: f32,
     pub upgrades: Vec<LauncherUpgrade>,
 }
 
@@ -31,30 +203,61 @@ impl Launcher {
     pub fn new() -> Self {
         Self {
             shoot_timer: Timer::new(0.25, false),
-            base_timer_duration: 0.25,
-            base_damage: 8.,
+            stats: Stats::new(&[(Stat::Damage, 8.), (Stat::Cooldown, 0.25)]),
+            firing: false,
+            charge: 0.,
             upgrades: vec![],
         }
     }
 
     pub fn get_upgraded_data(&self) -> LauncherData {
-        let mut damage = self.base_damage;
         let mut double_bullets = false;
-        let mut duration_percentage_decrease = 0.;
+        let mut barrage = false;
+        let mut modifiers = ProjectileModifiers {
+            pierce: self.stats.get(Stat::Pierce) as u32,
+            bounces: self.stats.get(Stat::Bounces) as u32,
+            ..Default::default()
+        };
         for upgrade in &self.upgrades {
             match upgrade {
-                LauncherUpgrade::FireRate(rate) => {
-                    duration_percentage_decrease += rate;
-                }
-                LauncherUpgrade::Damage(dmg) => damage += dmg,
                 LauncherUpgrade::DoubleBullet => double_bullets = true,
+                LauncherUpgrade::Barrage => barrage = true,
+                LauncherUpgrade::Homing => {
+                    modifiers.homing = Some(Homing {
+                        turn_speed: 4.,
+                        range: 80.,
+                    })
+                }
+                LauncherUpgrade::Fragment => {
+                    modifiers.split = Some(SplitOnImpact {
+                        count: 3,
+                        speed: 90.,
+                        damage_multiplier: 0.4,
+                    });
+                    // Fragments fly a short way so they don't fill the room
+                    modifiers.range.get_or_insert(220.);
+                }
+                _ => {}
             }
         }
 
+        let mut statuses = vec![];
+        let burn = self.stats.get(Stat::Burn);
+        if burn > 0. {
+            statuses.push(StatusEffect::new(StatusKind::Burn, 3., burn));
+        }
+        let poison = self.stats.get(Stat::Poison);
+        if poison > 0. {
+            statuses.push(StatusEffect::new(StatusKind::Poison, 4., poison));
+        }
+
         LauncherData {
-            damage,
-            timer_duration: self.base_timer_duration * (1. - duration_percentage_decrease),
+            damage: self.stats.get(Stat::Damage),
+            timer_duration: self.stats.get(Stat::Cooldown),
             double_bullets,
+            barrage,
+            statuses,
+            modifiers,
         }
     }
 }
@@ -63,14 +266,161 @@ pub struct LauncherData {
     pub damage: f32,
     pub timer_duration: f32,
     pub double_bullets: bool,
+    /// Adds a third bullet between the double bullets.
+    pub barrage: bool,
+    pub statuses: Vec<StatusEffect>,
+    pub modifiers: ProjectileModifiers,
+}
+
+impl WeaponBehavior for Launcher {
+    fn weapon_type(&self) -> WeaponType {
+        WeaponType::Launcher
+    }
+
+    fn hud_icon(&self) -> &'static str {
+        "upgrade_launcher"
+    }
+
+    fn stats(&self) -> WeaponStats {
+        let data = self.get_upgraded_data();
+        WeaponStats {
+            damage: data.damage,
+            cooldown: data.timer_duration,
+            statuses: data.statuses,
+        }
+    }
+
+    fn stat_sheet(&self) -> &Stats {
+        &self.stats
+    }
+
+    fn update(&mut self, ctx: &WeaponContext, _ecs: &mut Ecs) {
+        self.shoot_timer.update();
+        // A tap toggles, a longer hold charges instead
+        if ctx.fire_mode == FireMode::Toggle
+            && ctx.input.is_just_released(Action::Fire)
+            && self.charge < LAUNCHER_MIN_CHARGE
+        {
+            self.firing = !self.firing;
+        }
+    }
+
+    fn on_unequip(&mut self, _ecs: &mut Ecs, _player_e: Entity) {
+        self.charge = 0.;
+        self.firing = false;
+    }
+
+    fn charge(&self) -> Option<f32> {
+        if self.charge > 0. {
+            Some(self.charge)
+        } else {
+            None
+        }
+    }
+
+    fn on_fire(&mut self, ctx: &WeaponContext, _ecs: &mut Ecs) -> Vec<Shot> {
+        let launcher_data = self.get_upgraded_data();
+        self.shoot_timer.time = launcher_data.timer_duration;
+        let dir = ctx.aim_dir;
+
+        // Holding fire charges a piercing shot that's released with the button. Regular fire
+        // pauses meanwhile, except in Hold mode where holding fire is also the trigger.
+        let fire_held = ctx.input.is_currently_pressed(Action::Fire);
+        if fire_held || ctx.input.is_currently_pressed(Action::AltFire) {
+            self.charge = (self.charge + get_frame_time() / LAUNCHER_CHARGE_TIME).min(1.);
+            if !(fire_held && ctx.fire_mode == FireMode::Hold) {
+                return vec![];
+            }
+        } else if self.charge > 0. {
+            let charge = self.charge;
+            self.charge = 0.;
+            if charge >= LAUNCHER_MIN_CHARGE {
+                self.shoot_timer.reset();
+                let mut modifiers = launcher_data.modifiers.clone();
+                modifiers.pierce += LAUNCHER_CHARGE_PIERCE;
+                return vec![Shot::new(
+                    launcher_data.damage * (1. + 3. * charge),
+                    ctx.player_pos + dir * 4.,
+                    dir * 200.,
+                )
+                .with_modifiers(modifiers)
+                .scaled(1. + charge)];
+            }
+        }
+
+        if !ctx.trigger_held(self.firing) || !self.shoot_timer.completed() {
+            return vec![];
+        }
+        self.shoot_timer.reset();
+
+        if launcher_data.double_bullets {
+            let angle = (dir.y / dir.x).atan();
+            let mut shots = vec![
+                Shot::new(
+                    launcher_data.damage,
+                    ctx.player_pos + Vec2::from_angle(angle + TAU / 6.) * 3.,
+                    dir * 160.,
+                )
+                .with_modifiers(launcher_data.modifiers.clone()),
+                Shot::new(
+                    launcher_data.damage,
+                    ctx.player_pos + Vec2::from_angle(angle - TAU / 6.) * 3.,
+                    dir * 160.,
+                )
+                .with_modifiers(launcher_data.modifiers.clone()),
+            ];
+            if launcher_data.barrage {
+                shots.push(
+                    Shot::new(launcher_data.damage, ctx.player_pos + dir * 5., dir * 180.)
+                        .with_modifiers(launcher_data.modifiers),
+                );
+            }
+            shots
+        } else {
+            vec![
+                Shot::new(launcher_data.damage, ctx.player_pos + dir * 3., dir * 160.)
+                    .with_modifiers(launcher_data.modifiers),
+            ]
+        }
+    }
+
+    fn upgrade_pool(&self) -> Vec<WeaponUpgrade> {
+        vec![
+            LauncherUpgrade::FireRate(0.05),
+            LauncherUpgrade::Damage(3.),
+            LauncherUpgrade::DoubleBullet,
+            LauncherUpgrade::Burn(4.),
+            LauncherUpgrade::Poison(2.),
+            LauncherUpgrade::Pierce(1),
+            LauncherUpgrade::Ricochet(1),
+            LauncherUpgrade::Homing,
+            LauncherUpgrade::Fragment,
+        ]
+        .into_iter()
+        .map(WeaponUpgrade::Launcher)
+        .collect()
+    }
+
+    fn apply_upgrade(&mut self, upgrade: &WeaponUpgrade) {
+        if let WeaponUpgrade::Launcher(upgrade) = upgrade {
+            self.stats.add(upgrade.modifiers());
+            self.upgrades.push(upgrade.clone());
+        }
+    }
+
+    fn taken_upgrades(&self) -> Vec<WeaponUpgrade> {
+        self.upgrades
+            .iter()
+            .cloned()
+            .map(WeaponUpgrade::Launcher)
+            .collect()
+    }
 }
 
 pub struct Balls {
     pub ball_spawn_timer: Timer,
-    pub base_amount: usize,
-    pub base_damage: f32,
+    pub stats: Stats,
     pub rotation_progress: f32,
-    pub base_rotation_speed: f32,
     pub buffered_spawns: usize,
     pub upgrades: Vec<BallsUpgrade>,
 }
@@ -80,51 +430,173 @@ pub struct BallsData {
     pub damage: f32,
     pub rotation_speed: f32,
     pub bullets: bool,
+    pub nova: bool,
+    pub statuses: Vec<StatusEffect>,
 }
 
 impl Balls {
     pub fn new() -> Self {
         Self {
             ball_spawn_timer: Timer::new(0.25, true),
-            base_amount: 3,
-            base_damage: 13.,
+            stats: Stats::new(&[
+                (Stat::Amount, 3.),
+                (Stat::Damage, 13.),
+                (Stat::RotationSpeed, 0.25),
+            ]),
             rotation_progress: 0.,
             upgrades: vec![],
-            base_rotation_speed: 0.25,
             buffered_spawns: 3,
         }
     }
 
-    pub fn update(&mut self) {
+    pub fn get_upgraded_data(&self) -> BallsData {
+        let mut statuses = vec![];
+        let slow = self.stats.get(Stat::Slow);
+        if slow > 0. {
+            statuses.push(StatusEffect::new(StatusKind::Slow, 1.5, slow));
+        }
+
+        BallsData {
+            amount: self.stats.get(Stat::Amount) as usize,
+            damage: self.stats.get(Stat::Damage),
+            rotation_speed: self.stats.get(Stat::RotationSpeed),
+            bullets: self
+                .upgrades
+                .iter()
+                .any(|upgrade| matches!(upgrade, BallsUpgrade::Split)),
+            nova: self
+                .upgrades
+                .iter()
+                .any(|upgrade| matches!(upgrade, BallsUpgrade::Nova)),
+            statuses,
+        }
+    }
+
+    fn ball_entities(ecs: &Ecs) -> Vec<Entity> {
+        ecs.check_components(|e, comps| {
+            comps.balls.contains_key(e)
+                && comps.positions.contains_key(e)
+                && comps.velocities.contains_key(e)
+        })
+    }
+}
+
+impl WeaponBehavior for Balls {
+    fn weapon_type(&self) -> WeaponType {
+        WeaponType::Balls
+    }
+
+    fn hud_icon(&self) -> &'static str {
+        "upgrade_balls"
+    }
+
+    fn stats(&self) -> WeaponStats {
+        let data = self.get_upgraded_data();
+        WeaponStats {
+            damage: data.damage,
+            cooldown: self.ball_spawn_timer.time,
+            statuses: data.statuses,
+        }
+    }
+
+    fn stat_sheet(&self) -> &Stats {
+        &self.stats
+    }
+
+    fn update(&mut self, ctx: &WeaponContext, ecs: &mut Ecs) {
         let data = self.get_upgraded_data();
         self.rotation_progress += get_frame_time() * (TAU * data.rotation_speed);
         self.ball_spawn_timer.update();
         if self.ball_spawn_timer.just_completed() {
             self.buffered_spawns = (self.buffered_spawns + 1).min(data.amount);
         }
-    }
 
-    pub fn get_upgraded_data(&self) -> BallsData {
-        let mut amount = self.base_amount;
-        let mut damage = self.base_damage;
-        let mut rotation_speed_percentage = 0.;
-        let mut bullets = false;
-        for upgrade in &self.upgrades {
-            match upgrade {
-                BallsUpgrade::Amount(added_amount) => amount += added_amount,
-                BallsUpgrade::Damage(dmg) => damage += dmg,
-                BallsUpgrade::RotateSpeed(speed) => rotation_speed_percentage += speed,
-                BallsUpgrade::Split => bullets = true,
+        for ball_e in &Balls::ball_entities(ecs) {
+            let position = ecs.components.positions.get_mut(ball_e).unwrap();
+            let ball_index = ecs.components.balls.get(ball_e).unwrap();
+
+            let angle = Vec2::from_angle(
+                TAU / data.amount as f32 * *ball_index as f32 + TAU * self.rotation_progress,
+            )
+            .rotate(Vec2::X);
+
+            let ball_distance = 24.;
+            *position = ctx.player_pos + angle * ball_distance;
+
+            // Balls stay out between spawns, so damage upgrades also reach the ones already circling
+            if let Some(damage) = ecs.components.damage_on_collision.get_mut(ball_e) {
+                damage.damage = data.damage;
             }
         }
+    }
 
-        BallsData {
-            amount,
-            damage,
-            rotation_speed: self.base_rotation_speed * (1. + rotation_speed_percentage),
-            bullets,
+    fn on_fire(&mut self, ctx: &WeaponContext, ecs: &mut Ecs) -> Vec<Shot> {
+        let balls_data = self.get_upgraded_data();
+        let ball_entities = Balls::ball_entities(ecs);
+
+        if ball_entities.len() >= balls_data.amount || self.buffered_spawns == 0 {
+            return vec![];
+        }
+
+        let mut ball_ids = (0..balls_data.amount).collect::<Vec<usize>>();
+        for ball_e in &ball_entities {
+            let id = ecs.components.balls.get(ball_e).unwrap();
+            ball_ids.retain(|b_id| b_id != id);
+        }
+        self.buffered_spawns -= 1;
+        self.ball_spawn_timer.reset();
+
+        vec![Shot {
+            damage: self.stats.get(Stat::Damage),
+            position: ctx.player_pos,
+            velocity: Vec2::ZERO,
+            ball_index: Some(ball_ids[0]),
+            modifiers: ProjectileModifiers::default(),
+            scale: 1.,
+        }]
+    }
+
+    fn on_hit(&mut self, position: Vec2, collider: &ColliderType) -> Vec<Shot> {
+        let balls_data = self.get_upgraded_data();
+        if *collider == ColliderType::ProjectileWithoutMapCollision && balls_data.bullets {
+            return split_shots(position, if balls_data.nova { 8 } else { 4 });
+        }
+        vec![]
+    }
+
+    fn on_unequip(&mut self, ecs: &mut Ecs, _player_e: Entity) {
+        for ball_e in Balls::ball_entities(ecs) {
+            ecs.despawn(ball_e);
+        }
+    }
+
+    fn upgrade_pool(&self) -> Vec<WeaponUpgrade> {
+        vec![
+            BallsUpgrade::Amount(2),
+            BallsUpgrade::Damage(18.),
+            BallsUpgrade::RotateSpeed(0.2),
+            BallsUpgrade::Split,
+            BallsUpgrade::Frost(0.15),
+        ]
+        .into_iter()
+        .map(WeaponUpgrade::Balls)
+        .collect()
+    }
+
+    fn apply_upgrade(&mut self, upgrade: &WeaponUpgrade) {
+        if let WeaponUpgrade::Balls(upgrade) = upgrade {
+            self.stats.add(upgrade.modifiers());
+            self.upgrades.push(upgrade.clone());
         }
     }
+
+    fn taken_upgrades(&self) -> Vec<WeaponUpgrade> {
+        self.upgrades
+            .iter()
+            .cloned()
+            .map(WeaponUpgrade::Balls)
+            .collect()
+    }
 }
 
 pub struct Dash {
@@ -136,14 +608,15 @@ pub struct Dash {
     pub shadow_timer: Timer,
     pub shadow_index: usize,
     pub upgrades: Vec<DashUpgrade>,
-    pub base_damage: f32,
-    pub base_dash_timer_duration: f32,
+    pub stats: Stats,
 }
 
 pub struct DashData {
     pub damage: f32,
     pub dash_timer_duration: f32,
     pub bullets: bool,
+    pub comet: bool,
+    pub statuses: Vec<StatusEffect>,
 }
 
 impl Dash {
@@ -151,7 +624,6 @@ impl Dash {
         let dash_timer_duration = 0.6;
         Self {
             dash_timer: Timer::new(dash_timer_duration, false),
-            base_dash_timer_duration: dash_timer_duration,
             dashing_timer: Timer::new(0.24, false),
             shadow_timer: Timer::new(0.08, false),
             speed: 160.,
@@ -159,33 +631,758 @@ impl Dash {
             direction: Vec2::X,
             shadow_index: 0,
             upgrades: vec![],
-            base_damage: 24.,
+            stats: Stats::new(&[(Stat::Damage, 24.), (Stat::Cooldown, dash_timer_duration)]),
+        }
+    }
+
+    pub fn get_upgraded_data(&self) -> DashData {
+        let mut statuses = vec![];
+        let stun = self.stats.get(Stat::Stun);
+        if stun > 0. {
+            statuses.push(StatusEffect::new(StatusKind::Stun, stun, 0.));
+        }
+
+        DashData {
+            damage: self.stats.get(Stat::Damage),
+            dash_timer_duration: self.stats.get(Stat::Cooldown),
+            bullets: self
+                .upgrades
+                .iter()
+                .any(|upgrade| matches!(upgrade, DashUpgrade::Bullets)),
+            comet: self
+                .upgrades
+                .iter()
+                .any(|upgrade| matches!(upgrade, DashUpgrade::Comet)),
+            statuses,
         }
     }
 
-    pub fn update(&mut self) {
+    fn end_dash(&mut self, ecs: &mut Ecs, player_e: Entity) {
+        self.dashing = false;
+        if let Some(player_data) = ecs.components.player_data.get(&player_e) {
+            for shadow_e in &player_data.shadows {
+                if let Some(sprite) = ecs.components.animated_sprites.get_mut(shadow_e) {
+                    sprite.visible = false;
+                }
+            }
+        }
+        ecs.components.damage_on_collision.remove(&player_e);
+        ecs.components.status_on_hit.remove(&player_e);
+    }
+}
+
+impl WeaponBehavior for Dash {
+    fn weapon_type(&self) -> WeaponType {
+        WeaponType::Dash
+    }
+
+    fn hud_icon(&self) -> &'static str {
+        "upgrade_dash"
+    }
+
+    fn stats(&self) -> WeaponStats {
+        let data = self.get_upgraded_data();
+        WeaponStats {
+            damage: data.damage,
+            cooldown: data.dash_timer_duration,
+            statuses: data.statuses,
+        }
+    }
+
+    fn stat_sheet(&self) -> &Stats {
+        &self.stats
+    }
+
+    fn update(&mut self, ctx: &WeaponContext, ecs: &mut Ecs) {
         self.dash_timer.update();
         self.dashing_timer.update();
         self.shadow_timer.update();
+
+        if self.dashing_timer.just_completed() {
+            self.end_dash(ecs, ctx.player_e);
+        }
+        if self.shadow_timer.just_completed() && self.shadow_index < 2 {
+            let player_data = ecs.components.player_data.get(&ctx.player_e).unwrap();
+            let shadow_e = player_data.shadows[self.shadow_index];
+            let sprite = ecs.components.animated_sprites.get_mut(&shadow_e).unwrap();
+            let shadow_pos = ecs.components.positions.get_mut(&shadow_e).unwrap();
+            sprite.visible = true;
+            *shadow_pos = ctx.player_pos;
+            self.shadow_timer.reset();
+            self.shadow_index += 1;
+        }
     }
 
-    pub fn get_upgraded_data(&self) -> DashData {
-        let mut damage = self.base_damage;
-        let mut bullets = false;
-        let mut duration_percentage_decrease = 0.;
-        for upgrade in &self.upgrades {
-            match upgrade {
-                DashUpgrade::Damage(increase) => damage += increase,
-                DashUpgrade::TimerDecrease(decrease) => duration_percentage_decrease += decrease,
-                DashUpgrade::Bullets => bullets = true,
+    fn on_fire(&mut self, ctx: &WeaponContext, ecs: &mut Ecs) -> Vec<Shot> {
+        let dash_data = self.get_upgraded_data();
+        self.dash_timer.time = dash_data.dash_timer_duration;
+        if !self.dash_timer.completed() || !ctx.input.is_just_pressed(Action::Confirm) {
+            return vec![];
+        }
+
+        self.dash_timer.reset();
+        self.dashing_timer.reset();
+        self.shadow_timer.reset();
+        ecs.components.damage_on_collision.insert(
+            ctx.player_e,
+            DamageOnCollision {
+                source: EntityType::Player,
+                damage: dash_data.damage,
+                damage_type: DamageType::Physical,
+                knockback: 140.,
+            },
+        );
+        if !dash_data.statuses.is_empty() {
+            ecs.components
+                .status_on_hit
+                .insert(ctx.player_e, dash_data.statuses);
+        }
+        self.dashing = true;
+        self.shadow_index = 0;
+
+        let mut dir = Vec2::ZERO;
+        if ctx.input.is_currently_pressed(Action::Left) {
+            dir += vec2(-1., 0.);
+        }
+        if ctx.input.is_currently_pressed(Action::Up) {
+            dir += vec2(0., -1.);
+        }
+        if ctx.input.is_currently_pressed(Action::Right) {
+            dir += vec2(1., 0.);
+        }
+        if ctx.input.is_currently_pressed(Action::Down) {
+            dir += vec2(0., 1.);
+        }
+        if let Some(gamepad) = ctx.input.gamepads.get_last_used() {
+            let input = vec2(gamepad.left_stick_x(), -gamepad.left_stick_y());
+            if input.length_squared() > 0. {
+                dir = input;
             }
         }
 
-        DashData {
-            damage,
-            dash_timer_duration: self.base_dash_timer_duration
-                * (1. - duration_percentage_decrease),
-            bullets,
+        self.direction = if dir.length_squared() > 0. {
+            dir.normalize()
+        } else {
+            Vec2::X
+        };
+
+        vec![]
+    }
+
+    fn on_hit(&mut self, position: Vec2, _collider: &ColliderType) -> Vec<Shot> {
+        let dash_data = self.get_upgraded_data();
+        if dash_data.bullets {
+            return split_shots(position, if dash_data.comet { 8 } else { 4 });
+        }
+        vec![]
+    }
+
+    fn on_unequip(&mut self, ecs: &mut Ecs, player_e: Entity) {
+        self.end_dash(ecs, player_e);
+    }
+
+    fn evading(&self) -> bool {
+        self.dashing
+    }
+
+    fn movement_override(&self) -> Option<Vec2> {
+        if self.dashing {
+            Some(self.direction * self.speed)
+        } else {
+            None
+        }
+    }
+
+    fn upgrade_pool(&self) -> Vec<WeaponUpgrade> {
+        vec![
+            DashUpgrade::Damage(30.),
+            DashUpgrade::TimerDecrease(0.15),
+            DashUpgrade::Bullets,
+            DashUpgrade::Stun(0.6),
+        ]
+        .into_iter()
+        .map(WeaponUpgrade::Dash)
+        .collect()
+    }
+
+    fn apply_upgrade(&mut self, upgrade: &WeaponUpgrade) {
+        if let WeaponUpgrade::Dash(upgrade) = upgrade {
+            self.stats.add(upgrade.modifiers());
+            self.upgrades.push(upgrade.clone());
+        }
+    }
+
+    fn taken_upgrades(&self) -> Vec<WeaponUpgrade> {
+        self.upgrades
+            .iter()
+            .cloned()
+            .map(WeaponUpgrade::Dash)
+            .collect()
+    }
+}
+
+const BEAM_TICK: f32 = 0.15;
+const BEAM_WIDTH: f32 = 3.;
+
+pub struct Beam {
+    pub stats: Stats,
+    pub heat_per_second: f32,
+    pub heat: f32,
+    pub overheated: bool,
+    pub firing: bool,
+    pub active: bool,
+    pub direction: Vec2,
+    pub start: Vec2,
+    pub end: Vec2,
+    pub tick_timer: Timer,
+    pub upgrades: Vec<BeamUpgrade>,
+}
+
+pub struct BeamData {
+    pub dps: f32,
+    pub range: f32,
+    pub turn_speed: f32,
+    pub cooling_per_second: f32,
+    pub statuses: Vec<StatusEffect>,
+}
+
+impl Beam {
+    pub fn new() -> Self {
+        Self {
+            stats: Stats::new(&[
+                (Stat::Damage, 40.),
+                (Stat::Range, 110.),
+                (Stat::RotationSpeed, 5.),
+                (Stat::Cooling, 0.5),
+            ]),
+            heat_per_second: 0.35,
+            heat: 0.,
+            overheated: false,
+            firing: false,
+            active: false,
+            direction: Vec2::X,
+            start: Vec2::ZERO,
+            end: Vec2::ZERO,
+            tick_timer: Timer::new(BEAM_TICK, true),
+            upgrades: vec![],
         }
     }
+
+    pub fn get_upgraded_data(&self) -> BeamData {
+        let mut statuses = vec![];
+        let burn = self.stats.get(Stat::Burn);
+        if burn > 0. {
+            statuses.push(StatusEffect::new(StatusKind::Burn, 2., burn));
+        }
+
+        BeamData {
+            dps: self.stats.get(Stat::Damage),
+            range: self.stats.get(Stat::Range),
+            turn_speed: self.stats.get(Stat::RotationSpeed),
+            cooling_per_second: self.stats.get(Stat::Cooling),
+            statuses,
+        }
+    }
+
+    /// Enemies whose collider overlaps the beam segment.
+    fn hit_entities(&self, ecs: &Ecs) -> Vec<Entity> {
+        let segment = self.end - self.start;
+        let length_squared = segment.length_squared();
+        ecs.check_components(|e, comps| {
+            let (Some(position), Some(collider)) = (comps.positions.get(e), comps.colliders.get(e))
+            else {
+                return false;
+            };
+            if !comps.enemies.contains_key(e)
+                || !comps.health.contains_key(e)
+                || collider.coll_type != ColliderType::Enemy
+            {
+                return false;
+            }
+            let t = if length_squared > 0. {
+                ((*position - self.start).dot(segment) / length_squared).clamp(0., 1.)
+            } else {
+                0.
+            };
+            let closest = self.start + segment * t;
+            closest.distance(*position) <= collider.radius + BEAM_WIDTH / 2.
+        })
+    }
+}
+
+impl WeaponBehavior for Beam {
+    fn weapon_type(&self) -> WeaponType {
+        WeaponType::Beam
+    }
+
+    fn hud_icon(&self) -> &'static str {
+        "upgrade_beam"
+    }
+
+    fn stats(&self) -> WeaponStats {
+        let data = self.get_upgraded_data();
+        WeaponStats {
+            damage: data.dps * BEAM_TICK,
+            cooldown: BEAM_TICK,
+            statuses: data.statuses,
+        }
+    }
+
+    fn stat_sheet(&self) -> &Stats {
+        &self.stats
+    }
+
+    fn update(&mut self, ctx: &WeaponContext, _ecs: &mut Ecs) {
+        let beam_data = self.get_upgraded_data();
+        if ctx.fire_mode == FireMode::Toggle && ctx.input.is_just_pressed(Action::Fire) {
+            self.firing = !self.firing;
+        }
+
+        self.active = ctx.trigger_held(self.firing) && !self.overheated;
+        if self.active {
+            self.heat = (self.heat + self.heat_per_second * get_frame_time()).min(1.);
+            if self.heat >= 1. {
+                self.overheated = true;
+            }
+        } else {
+            self.heat = (self.heat - beam_data.cooling_per_second * get_frame_time()).max(0.);
+            // Overheating locks the beam until it has fully cooled down
+            if self.heat <= 0. {
+                self.overheated = false;
+            }
+        }
+
+        // The beam sweeps towards the aim direction instead of snapping to it
+        let max_turn = beam_data.turn_speed * get_frame_time();
+        let angle = self.direction.angle_between(ctx.aim_dir);
+        self.direction = Vec2::from_angle(angle.clamp(-max_turn, max_turn))
+            .rotate(self.direction)
+            .normalize_or_zero();
+        if self.direction == Vec2::ZERO {
+            self.direction = ctx.aim_dir;
+        }
+
+        self.start = ctx.player_pos + self.direction * 4.;
+        let target = self.start + self.direction * beam_data.range;
+        self.end = match ctx.map.raycast(self.start, target) {
+            Some(hit) => hit.point,
+            None => target,
+        };
+
+        if !self.active {
+            self.tick_timer.reset();
+            return;
+        }
+        self.tick_timer.update();
+    }
+
+    fn on_fire(&mut self, _ctx: &WeaponContext, _ecs: &mut Ecs) -> Vec<Shot> {
+        vec![]
+    }
+
+    fn strike(&mut self, ctx: &WeaponContext, ecs: &mut Ecs) -> Vec<DamageEvent> {
+        if !self.active || !self.tick_timer.just_completed() {
+            return vec![];
+        }
+        let beam_data = self.get_upgraded_data();
+        self.hit_entities(ecs)
+            .into_iter()
+            .map(|enemy_e| DamageEvent {
+                source: ctx.player_e,
+                target: enemy_e,
+                damage: Damage::new(beam_data.dps * BEAM_TICK, DamageType::Fire),
+                statuses: beam_data.statuses.clone(),
+                impulse: None,
+                continuous: true,
+            })
+            .collect()
+    }
+
+    fn on_unequip(&mut self, _ecs: &mut Ecs, _player_e: Entity) {
+        self.active = false;
+        self.firing = false;
+    }
+
+    fn draw(&self) {
+        if !self.active {
+            return;
+        }
+        let flicker = (self.tick_timer.progress() * 0.5 + 0.5).clamp(0., 1.);
+        draw_line(
+            self.start.x,
+            self.start.y,
+            self.end.x,
+            self.end.y,
+            BEAM_WIDTH,
+            Color::from_rgba(120, 200, 255, (140. + 80. * flicker) as u8),
+        );
+        draw_line(
+            self.start.x,
+            self.start.y,
+            self.end.x,
+            self.end.y,
+            1.,
+            Color::from_rgba(235, 250, 255, 255),
+        );
+        draw_circle(
+            self.end.x,
+            self.end.y,
+            2. + flicker,
+            Color::from_rgba(235, 250, 255, 220),
+        );
+    }
+
+    fn heat(&self) -> Option<(f32, bool)> {
+        if self.heat > 0. {
+            Some((self.heat, self.overheated))
+        } else {
+            None
+        }
+    }
+
+    fn upgrade_pool(&self) -> Vec<WeaponUpgrade> {
+        vec![
+            BeamUpgrade::Damage(10.),
+            BeamUpgrade::Range(24.),
+            BeamUpgrade::TurnSpeed(0.3),
+            BeamUpgrade::Cooling(0.25),
+            BeamUpgrade::Ignite,
+        ]
+        .into_iter()
+        .map(WeaponUpgrade::Beam)
+        .collect()
+    }
+
+    fn apply_upgrade(&mut self, upgrade: &WeaponUpgrade) {
+        if let WeaponUpgrade::Beam(upgrade) = upgrade {
+            self.stats.add(upgrade.modifiers());
+            self.upgrades.push(upgrade.clone());
+        }
+    }
+
+    fn taken_upgrades(&self) -> Vec<WeaponUpgrade> {
+        self.upgrades
+            .iter()
+            .cloned()
+            .map(WeaponUpgrade::Beam)
+            .collect()
+    }
+}
+
+const MELEE_COMBO_DAMAGE: [f32; 3] = [1., 1.4, 2.2];
+const MELEE_COMBO_KNOCKBACK: [f32; 3] = [80., 120., 240.];
+const MELEE_REFLECT_SPEED: f32 = 180.;
+
+pub struct Melee {
+    pub stats: Stats,
+    /// Half of the swept angle in radians.
+    pub arc: f32,
+    pub swing_timer: Timer,
+    pub recovery_timer: Timer,
+    /// Swinging again before this runs out continues the combo.
+    pub combo_timer: Timer,
+    pub combo: usize,
+    pub chain_open: bool,
+    pub swinging: bool,
+    pub firing: bool,
+    pub direction: Vec2,
+    pub origin: Vec2,
+    pub hit: Vec<Entity>,
+    pub upgrades: Vec<MeleeUpgrade>,
+}
+
+pub struct MeleeData {
+    pub damage: f32,
+    pub range: f32,
+    pub recovery_duration: f32,
+    pub shockwave: bool,
+    /// Every hit of the combo sends out a shockwave.
+    pub quake: bool,
+    pub statuses: Vec<StatusEffect>,
+}
+
+impl Melee {
+    pub fn new() -> Self {
+        let recovery_duration = 0.3;
+        let mut recovery_timer = Timer::new(recovery_duration, false);
+        recovery_timer.stop();
+        Self {
+            stats: Stats::new(&[
+                (Stat::Damage, 14.),
+                (Stat::Range, 22.),
+                (Stat::Cooldown, recovery_duration),
+            ]),
+            arc: 1.1,
+            swing_timer: Timer::new(0.12, false),
+            recovery_timer,
+            combo_timer: Timer::new(0.45, false),
+            combo: 0,
+            chain_open: false,
+            swinging: false,
+            firing: false,
+            direction: Vec2::X,
+            origin: Vec2::ZERO,
+            hit: vec![],
+            upgrades: vec![],
+        }
+    }
+
+    pub fn get_upgraded_data(&self) -> MeleeData {
+        let mut statuses = vec![];
+        let stun = self.stats.get(Stat::Stun);
+        if stun > 0. {
+            statuses.push(StatusEffect::new(StatusKind::Stun, stun, 0.));
+        }
+
+        MeleeData {
+            damage: self.stats.get(Stat::Damage),
+            range: self.stats.get(Stat::Range),
+            // Swing speed upgrades can't cut the recovery below 30%
+            recovery_duration: self
+                .stats
+                .get(Stat::Cooldown)
+                .max(self.stats.base(Stat::Cooldown) * 0.3),
+            shockwave: self
+                .upgrades
+                .iter()
+                .any(|upgrade| matches!(upgrade, MeleeUpgrade::Shockwave)),
+            quake: self
+                .upgrades
+                .iter()
+                .any(|upgrade| matches!(upgrade, MeleeUpgrade::Quake)),
+            statuses,
+        }
+    }
+
+    fn is_final_hit(&self) -> bool {
+        self.combo == MELEE_COMBO_DAMAGE.len() - 1
+    }
+
+    fn in_arc(&self, position: Vec2, radius: f32, range: f32) -> bool {
+        let diff = position - self.origin;
+        if diff.length() > range + radius {
+            return false;
+        }
+        diff.length_squared() <= 0. || self.direction.angle_between(diff).abs() <= self.arc
+    }
+
+    /// Turns enemy bullets inside the arc into player bullets flying along the swing.
+    fn reflect_bullets(&self, ecs: &mut Ecs, range: f32) {
+        let bullets = ecs.check_components(|e, comps| {
+            comps.positions.contains_key(e)
+                && comps.velocities.contains_key(e)
+                && comps
+                    .despawn_on_hit
+                    .get(e)
+                    .map_or(false, |despawn| despawn.0 == EntityType::Player)
+                && comps
+                    .damage_on_collision
+                    .get(e)
+                    .map_or(false, |damage| damage.source == EntityType::Enemy)
+        });
+
+        for bullet_e in &bullets {
+            let position = *ecs.components.positions.get(bullet_e).unwrap();
+            if !self.in_arc(position, 2.5, range) {
+                continue;
+            }
+            *ecs.components.velocities.get_mut(bullet_e).unwrap() =
+                self.direction * MELEE_REFLECT_SPEED;
+            ecs.components.bullet_emitters.remove(bullet_e);
+            ecs.components.speed_curves.remove(bullet_e);
+            ecs.components
+                .despawn_on_hit
+                .insert(*bullet_e, DespawnOnHit(EntityType::Enemy));
+            if let Some(damage) = ecs.components.damage_on_collision.get_mut(bullet_e) {
+                damage.source = EntityType::Player;
+            }
+            if let Some(collider) = ecs.components.colliders.get_mut(bullet_e) {
+                collider.coll_type = ColliderType::PlayerProjectile;
+            }
+            if let Some(sprite) = ecs.components.animated_sprites.get_mut(bullet_e) {
+                sprite.indexed_sprite.texture = "bullet";
+            }
+        }
+    }
+}
+
+impl WeaponBehavior for Melee {
+    fn weapon_type(&self) -> WeaponType {
+        WeaponType::Melee
+    }
+
+    fn hud_icon(&self) -> &'static str {
+        "upgrade_melee"
+    }
+
+    fn stats(&self) -> WeaponStats {
+        let data = self.get_upgraded_data();
+        WeaponStats {
+            damage: data.damage,
+            cooldown: data.recovery_duration,
+            statuses: data.statuses,
+        }
+    }
+
+    fn stat_sheet(&self) -> &Stats {
+        &self.stats
+    }
+
+    fn update(&mut self, ctx: &WeaponContext, _ecs: &mut Ecs) {
+        self.swing_timer.update();
+        self.recovery_timer.update();
+        self.combo_timer.update();
+        self.origin = ctx.player_pos;
+
+        if ctx.fire_mode == FireMode::Toggle && ctx.input.is_just_pressed(Action::Fire) {
+            self.firing = !self.firing;
+        }
+
+        if self.swinging && self.swing_timer.completed() {
+            self.swinging = false;
+            self.chain_open = !self.is_final_hit();
+            self.combo_timer.reset();
+        }
+        if self.chain_open && self.combo_timer.completed() {
+            self.chain_open = false;
+        }
+    }
+
+    fn on_fire(&mut self, ctx: &WeaponContext, _ecs: &mut Ecs) -> Vec<Shot> {
+        let melee_data = self.get_upgraded_data();
+        if self.swinging || !self.recovery_timer.completed() || !ctx.trigger_held(self.firing) {
+            return vec![];
+        }
+
+        self.combo = if self.chain_open { self.combo + 1 } else { 0 };
+        self.chain_open = false;
+        self.swinging = true;
+        self.direction = ctx.aim_dir;
+        self.hit.clear();
+        self.swing_timer.reset();
+        // The combo finisher takes longer to recover from
+        self.recovery_timer.time = if self.is_final_hit() {
+            melee_data.recovery_duration * 2.
+        } else {
+            melee_data.recovery_duration
+        };
+        self.recovery_timer.reset();
+
+        if (self.is_final_hit() && melee_data.shockwave) || melee_data.quake {
+            return split_shots(ctx.player_pos + self.direction * melee_data.range, 4);
+        }
+        vec![]
+    }
+
+    fn strike(&mut self, ctx: &WeaponContext, ecs: &mut Ecs) -> Vec<DamageEvent> {
+        if !self.swinging {
+            return vec![];
+        }
+        let melee_data = self.get_upgraded_data();
+        if self.is_final_hit() {
+            self.reflect_bullets(ecs, melee_data.range);
+        }
+
+        let enemies = ecs.check_components(|e, comps| {
+            comps.enemies.contains_key(e)
+                && comps.health.contains_key(e)
+                && comps.positions.contains_key(e)
+                && comps
+                    .colliders
+                    .get(e)
+                    .map_or(false, |collider| collider.coll_type == ColliderType::Enemy)
+        });
+
+        let mut events = vec![];
+        for enemy_e in enemies {
+            if self.hit.contains(&enemy_e) {
+                continue;
+            }
+            let position = *ecs.components.positions.get(&enemy_e).unwrap();
+            let radius = ecs.components.colliders.get(&enemy_e).unwrap().radius;
+            if !self.in_arc(position, radius, melee_data.range) {
+                continue;
+            }
+            self.hit.push(enemy_e);
+            let mut knockback_dir = (position - self.origin).normalize_or_zero();
+            if knockback_dir == Vec2::ZERO {
+                knockback_dir = self.direction;
+            }
+            events.push(DamageEvent {
+                source: ctx.player_e,
+                target: enemy_e,
+                damage: Damage::physical(melee_data.damage * MELEE_COMBO_DAMAGE[self.combo]),
+                statuses: melee_data.statuses.clone(),
+                impulse: Some(knockback_dir * MELEE_COMBO_KNOCKBACK[self.combo]),
+                continuous: false,
+            });
+        }
+        events
+    }
+
+    fn on_unequip(&mut self, _ecs: &mut Ecs, _player_e: Entity) {
+        self.swinging = false;
+        self.chain_open = false;
+        self.firing = false;
+        self.combo = 0;
+    }
+
+    fn draw(&self) {
+        if !self.swinging {
+            return;
+        }
+        let range = self.get_upgraded_data().range;
+        let alpha = 0.35 + 0.15 * self.combo as f32;
+        let color = if self.is_final_hit() {
+            Color::new(1., 0.9, 0.6, alpha)
+        } else {
+            Color::new(1., 1., 1., alpha)
+        };
+        // The arc fills in over the course of the swing, alternating sides each hit
+        let segments = 6;
+        let swept = (1. - self.swing_timer.progress()) * 2. * self.arc;
+        let start_angle = if self.combo % 2 == 0 {
+            -self.arc
+        } else {
+            self.arc - swept
+        };
+        for i in 0..segments {
+            let a = start_angle + swept * i as f32 / segments as f32;
+            let b = start_angle + swept * (i + 1) as f32 / segments as f32;
+            draw_triangle(
+                self.origin,
+                self.origin + Vec2::from_angle(a).rotate(self.direction) * range,
+                self.origin + Vec2::from_angle(b).rotate(self.direction) * range,
+                color,
+            );
+        }
+    }
+
+    fn upgrade_pool(&self) -> Vec<WeaponUpgrade> {
+        vec![
+            MeleeUpgrade::Damage(6.),
+            MeleeUpgrade::Range(4.),
+            MeleeUpgrade::SwingSpeed(0.15),
+            MeleeUpgrade::Shockwave,
+            MeleeUpgrade::Stun(0.4),
+        ]
+        .into_iter()
+        .map(WeaponUpgrade::Melee)
+        .collect()
+    }
+
+    fn apply_upgrade(&mut self, upgrade: &WeaponUpgrade) {
+        if let WeaponUpgrade::Melee(upgrade) = upgrade {
+            self.stats.add(upgrade.modifiers());
+            self.upgrades.push(upgrade.clone());
+        }
+    }
+
+    fn taken_upgrades(&self) -> Vec<WeaponUpgrade> {
+        self.upgrades
+            .iter()
+            .cloned()
+            .map(WeaponUpgrade::Melee)
+            .collect()
+    }
 }
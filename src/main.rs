@@ -6,12 +6,11 @@ use entity::{
     events::{DamageEvent, DeathEvent},
     mirituhg::spawn_mirituhg,
     player::spawn_player,
-    upgrades::{CommonUpgrade, ItemUpgrade, Upgrade, WeaponUpgrade},
+    upgrades::{CommonUpgrade, ItemUpgrade, Upgrade},
 };
 use fps_counter::FPSCounter;
 use game_data::{Audio, GameMaterial};
 use game_state::GameState;
-use items::weapon::{Balls, Dash, Launcher, Weapon, WeaponType};
 use macroquad::{
     audio::{self, play_sound, set_sound_volume},
     miniquad::window::set_mouse_cursor,
@@ -23,35 +22,51 @@ use sprite::{
     aberration_material::create_aberration_material, flash_material::create_sprite_color_material,
 };
 use systems::{
+    ai::update_ai,
     collision::draw_colliders,
+    damage_number::{draw_damage_numbers, update_damage_numbers},
     damageable::{
         apply_damage, damage_on_collision, despawn_on_collision, flash_on_damage, handle_death,
         kill_entities, update_damageables,
     },
+    destructible::damage_map_tiles,
+    elite::{draw_elite_health_bars, update_elites},
+    emitter::{update_emitters, update_speed_curves},
     enemy::update_enemies,
     movement::move_entities,
+    navigation::update_navigation,
+    pickup::draw_weapon_pickups,
     player::update_player,
-    spawn::spawn_creatures,
+    projectile::update_projectiles,
+    spawn::{draw_spawn_markers, spawn_creatures},
     sprite::{draw_animated_sprites, update_animated_sprites},
+    status::update_status_effects,
+    tile_effect::apply_tile_effects,
     timer::update_timers,
-    weapon::update_weapon,
+    weapon::{draw_weapon, update_weapon},
 };
 use ui::{
-    hud::{create_aberration_meter_material, AberrationMeter, HudHearts},
+    end_game_screen::EndGameChoice,
+    hud::{create_aberration_meter_material, AberrationMeter, HudHearts, HudWeapon},
     icon,
     intro_screen::IntroScreen,
     mirituhg::HudMirituhg,
     pause_menu::pause_menu,
+    stats_overlay::draw_stats_overlay,
     ui_data::UIData,
-    upgrade_screen::UpgradeScreen,
+    upgrade_screen::{UpgradeChoice, UpgradeScreen},
 };
 
 use crate::{
+    bullet_pattern::BulletPatterns,
+    difficulty::DifficultyProfile,
     game_data::{GameData, Graphics},
     input_manager::Action,
     map::map::Map,
 };
 
+mod bullet_pattern;
+mod difficulty;
 mod entity;
 mod fps_counter;
 mod game_data;
@@ -64,6 +79,7 @@ mod rand_utils;
 mod room;
 mod settings;
 mod sprite;
+mod stats;
 mod systems;
 mod timer;
 mod ui;
@@ -142,6 +158,7 @@ async fn main() {
 
     let hud_heart_texture = load_texture_bytes(include_bytes!("../assets/ui/heart_01.png"));
     let hopper_texture = load_texture_bytes(include_bytes!("../assets/entities/hopper_01.png"));
+    let burrower_texture = load_texture_bytes(include_bytes!("../assets/entities/burrower.png"));
     let spitter_texture = load_texture_bytes(include_bytes!("../assets/entities/spitter.png"));
     let stomper_texture = load_texture_bytes(include_bytes!("../assets/entities/stomper.png"));
     let mirituhg_texture = load_texture_bytes(include_bytes!("../assets/entities/mirituhg.png"));
@@ -184,6 +201,10 @@ async fn main() {
     let upgrade_balls_texture =
         load_texture_bytes(include_bytes!("../assets/ui/upgrade_balls.png"));
     let upgrade_dash_texture = load_texture_bytes(include_bytes!("../assets/ui/upgrade_dash.png"));
+    let upgrade_beam_texture = load_texture_bytes(include_bytes!("../assets/ui/upgrade_beam.png"));
+    let upgrade_melee_texture =
+        load_texture_bytes(include_bytes!("../assets/ui/upgrade_melee.png"));
+    let upgrade_crit_texture = load_texture_bytes(include_bytes!("../assets/ui/upgrade_crit.png"));
     let upgrade_common_max_hp_texture =
         load_texture_bytes(include_bytes!("../assets/ui/upgrade_common_max_hp.png"));
     let upgrade_item_hp_texture =
@@ -217,6 +238,7 @@ async fn main() {
 
     let textures = HashMap::from([
         ("hopper", hopper_texture),
+        ("burrower", burrower_texture),
         ("spitter", spitter_texture),
         ("stomper", stomper_texture),
         ("mirituhg", mirituhg_texture),
@@ -240,7 +262,10 @@ async fn main() {
         ("upgrade_launcher", upgrade_launcher_texture),
         ("upgrade_balls", upgrade_balls_texture),
         ("upgrade_dash", upgrade_dash_texture),
+        ("upgrade_beam", upgrade_beam_texture),
+        ("upgrade_melee", upgrade_melee_texture),
         ("upgrade_common_max_hp", upgrade_common_max_hp_texture),
+        ("upgrade_crit", upgrade_crit_texture),
         ("upgrade_item_hp", upgrade_item_hp_texture),
         ("upgrade_item_anomaly_big", upgrade_item_anomaly_big_texture),
         (
@@ -373,6 +398,8 @@ async fn main() {
         audio,
         death_texture,
         end_game_screen_texture,
+        DifficultyProfile::from_json(include_str!("../assets/data/difficulty.json")),
+        BulletPatterns::from_json(include_str!("../assets/data/bullet_patterns.json")),
     );
     data.reset();
     data.settings.set_window_size(WindowSize::W1440);
@@ -384,6 +411,7 @@ async fn main() {
     let mut death_events = Vec::<DeathEvent>::new();
 
     let hud_hearts = HudHearts::new(&data);
+    let hud_weapon = HudWeapon;
     let hud_mirituhg = HudMirituhg::new(overlay_mirituhg_texture, boss_health_bar_texture);
     let aberration_meter = AberrationMeter::new(&data);
 
@@ -435,6 +463,11 @@ async fn main() {
                 spawn_mirituhg(&mut data, vec2(180., 120.), &mut ecs);
             }
 
+            #[cfg(debug_assertions)]
+            if is_key_pressed(KeyCode::F4) {
+                data.show_stats = !data.show_stats;
+            }
+
             if is_key_pressed(KeyCode::F3) {
                 upgrade_screen.visible = true;
                 data.paused = true;
@@ -526,25 +559,41 @@ async fn main() {
             }
 
             data.current_map().draw_base();
+            draw_spawn_markers(&ecs);
 
             if !data.paused {
+                data.damage_dealt.clear();
                 spawn_creatures(&mut data, &mut ecs);
                 update_timers(&mut ecs);
                 update_damageables(&mut ecs);
                 damage_on_collision(&ecs, &mut damage_events, &collisions);
                 despawn_on_collision(&mut data, &mut ecs, &collisions);
+                damage_map_tiles(&mut data, &mut ecs, &collisions);
                 kill_entities(&data, &mut ecs, &mut death_events);
                 handle_death(&mut data, &mut ecs, &death_events);
                 update_player(&mut data, &mut ecs);
-                update_weapon(&mut ecs, &mut data);
-                update_enemies(&mut data, &mut ecs, &mut damage_events);
+                update_weapon(&mut ecs, &mut data, &mut damage_events);
+                update_navigation(&mut data, &ecs);
+                update_ai(&mut data, &mut ecs, &mut damage_events);
+                update_enemies(&mut data, &mut ecs);
+                update_elites(&mut ecs);
+                update_emitters(&mut data, &mut ecs);
+                update_speed_curves(&mut ecs);
+                update_projectiles(&mut data, &mut ecs);
+                apply_tile_effects(&mut data, &mut ecs, &mut damage_events);
+                update_status_effects(&mut ecs, &mut damage_events);
                 apply_damage(&mut data, &mut ecs, &mut damage_events);
+                update_damage_numbers(&mut data, &mut ecs);
                 update_animated_sprites(&mut ecs);
                 collisions = move_entities(&mut data, &mut ecs);
 
                 flash_on_damage(&mut ecs);
             }
             draw_animated_sprites(&mut ecs, &data);
+            draw_weapon_pickups(&data, &ecs);
+            draw_elite_health_bars(&ecs);
+            draw_damage_numbers(&data, &ecs);
+            draw_weapon(&data, &ecs);
             data.current_map().draw_upper();
 
             data.screen_dimmer.update();
@@ -570,6 +619,7 @@ async fn main() {
             }
 
             hud_hearts.draw(&data, &ecs);
+            hud_weapon.draw(&data);
             aberration_meter.draw(&data, &ecs);
             hud_mirituhg.draw(&data, &ecs);
         }
@@ -589,10 +639,34 @@ async fn main() {
             }
         }
         if data.game_completed {
-            data.end_game_screen.update();
-            if data.end_game_screen.draw(&data) {
-                data.state = GameState::Intro;
-                data.game_completed = false;
+            data.end_game_screen
+                .update(&data.input, &data.audio, &data.settings);
+            match data.end_game_screen.draw(&data) {
+                Some(EndGameChoice::ContinueRun) => {
+                    data.game_completed = false;
+                    data.paused = false;
+                    data.bosses_defeated += 1;
+                    let remains =
+                        ecs.check_components(|e, comps| comps.mirituhg_death.contains_key(e));
+                    for remains_e in remains {
+                        ecs.despawn(remains_e);
+                    }
+                    for player_data in ecs.components.player_data.values_mut() {
+                        player_data.aberration = 0.;
+                    }
+                }
+                Some(EndGameChoice::Finish) => {
+                    data.current_room.despawn(&mut ecs);
+                    let players =
+                        ecs.check_components(|e, comps| comps.player_entity.contains_key(e));
+                    for player_e in players {
+                        ecs.despawn(player_e);
+                    }
+                    data.reset();
+                    data.game_completed = false;
+                    data.paused = false;
+                }
+                None => {}
             }
         }
 
@@ -605,6 +679,9 @@ async fn main() {
         if data.show_fps {
             fps_counter.update_and_draw(&mut data);
         }
+        if data.show_stats && data.state == GameState::Playing {
+            draw_stats_overlay(&data, &ecs);
+        }
 
         if data.current_room.completed && !data.map_change_requested && !data.game_completed {
             data.next_room(&mut ecs);
@@ -617,7 +694,30 @@ async fn main() {
                 }
             } else if upgrade_screen.visible {
                 upgrade_screen.upgrades = data.current_room.available_upgrades.clone();
-                if let Some(upgrade) = upgrade_screen.draw(&mut data) {
+                let chosen_upgrade = match upgrade_screen.draw(&mut data) {
+                    Some(UpgradeChoice::Pick(upgrade)) => Some(upgrade),
+                    Some(UpgradeChoice::Reroll) => {
+                        data.reroll_upgrades(&ecs);
+                        None
+                    }
+                    Some(UpgradeChoice::Banish(index)) => {
+                        data.banish_upgrade(&ecs, index);
+                        None
+                    }
+                    Some(UpgradeChoice::Skip) => {
+                        // Skipping lowers the anomaly a little instead
+                        let players =
+                            ecs.check_components(|e, comps| comps.player_data.contains_key(e));
+                        let player_data = ecs.components.player_data.get_mut(&players[0]).unwrap();
+                        player_data.aberration = (player_data.aberration - 0.05).max(0.);
+                        data.current_room.upgrade_chosen = true;
+                        upgrade_screen.visible = false;
+                        data.paused = false;
+                        None
+                    }
+                    None => None,
+                };
+                if let Some(upgrade) = chosen_upgrade {
                     let players =
                         ecs.check_components(|e, comps| comps.player_data.contains_key(e));
                     let player_data = ecs.components.player_data.get_mut(&players[0]).unwrap();
@@ -638,43 +738,22 @@ async fn main() {
                         Upgrade::CommonUpgrade(ref upgrade) => match upgrade {
                             CommonUpgrade::MaxHp(hp) => {
                                 health.hp += *hp as f32;
-                                player_data.upgrades.push(upgrade.clone())
+                                player_data.add_upgrade(upgrade.clone())
                             }
-                            CommonUpgrade::MoveSpeed(_) => {
-                                player_data.upgrades.push(upgrade.clone())
+                            CommonUpgrade::MoveSpeed(_)
+                            | CommonUpgrade::CritChance(_)
+                            | CommonUpgrade::CritDamage(_) => {
+                                player_data.add_upgrade(upgrade.clone())
                             }
                             CommonUpgrade::ItemDropChance(increase) => {
                                 data.item_drop_chance_increase += increase;
                             }
                         },
-                        Upgrade::Weapon(ref weapon) => match weapon {
-                            WeaponType::Launcher => {
-                                data.weapon = Weapon::Launcher(Launcher::new());
-                            }
-                            WeaponType::Balls => {
-                                data.weapon = Weapon::Balls(Balls::new());
-                            }
-                            WeaponType::Dash => {
-                                data.weapon = Weapon::Dash(Dash::new());
-                            }
-                        },
-                        Upgrade::WeaponUpgrade(ref upgrade) => match upgrade {
-                            WeaponUpgrade::Launcher(ref upgrade) => {
-                                if let Weapon::Launcher(ref mut launcher) = data.weapon {
-                                    launcher.upgrades.push(upgrade.clone());
-                                }
-                            }
-                            WeaponUpgrade::Balls(ref upgrade) => {
-                                if let Weapon::Balls(ref mut balls) = data.weapon {
-                                    balls.upgrades.push(upgrade.clone());
-                                }
-                            }
-                            WeaponUpgrade::Dash(ref upgrade) => {
-                                if let Weapon::Dash(ref mut dash) = data.weapon {
-                                    dash.upgrades.push(upgrade.clone());
-                                }
-                            }
-                        },
+                        Upgrade::Weapon(ref weapon) => {
+                            data.weapon.on_unequip(&mut ecs, players[0]);
+                            data.weapon = weapon.create();
+                        }
+                        Upgrade::WeaponUpgrade(ref upgrade) => data.weapon.apply_upgrade(upgrade),
                     }
 
                     data.current_room.upgrade_chosen = true;
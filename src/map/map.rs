@@ -1,58 +1,277 @@
 use std::collections::{HashMap, HashSet};
 
 use macroquad::prelude::*;
-use macroquad_tiled::Map as TiledMap;
+use macroquad_tiled::{Map as TiledMap, Tile};
 
 use crate::{entity::entity_id::Entity, settings::GameSettings};
 
+#[derive(Clone, Default, Debug)]
+pub struct TileEffect {
+    pub damage: Option<f32>,
+    pub slow: Option<f32>,
+    pub aberration: Option<f32>,
+    pub pit: bool,
+}
+
+impl TileEffect {
+    pub fn is_empty(&self) -> bool {
+        self.damage.is_none() && self.slow.is_none() && self.aberration.is_none() && !self.pit
+    }
+
+    pub fn merge(&mut self, other: &TileEffect) {
+        self.damage = match (self.damage, other.damage) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        self.slow = match (self.slow, other.slow) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.aberration = match (self.aberration, other.aberration) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        self.pit = self.pit || other.pit;
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum TileDrop {
+    Health,
+    AnomalySmall,
+    AnomalyBig,
+    Random,
+}
+
+#[derive(Clone, Debug)]
+pub struct DestructibleTile {
+    pub hp: f32,
+    pub drop: Option<TileDrop>,
+    pub collision: bool,
+    pub layers: Vec<String>,
+    pub last_hit_time: f64,
+}
+
+pub struct RaycastHit {
+    pub tile: (usize, usize),
+    pub point: Vec2,
+    pub distance: f32,
+}
+
 pub struct Map {
     pub id: Entity,
     pub tiled_map: TiledMap,
     pub tileset_collision_map: HashMap<String, HashSet<usize>>,
     pub map_collision: HashSet<(usize, usize)>,
+    pub tileset_effect_map: HashMap<String, HashMap<usize, TileEffect>>,
+    pub map_effects: HashMap<(usize, usize), TileEffect>,
+    pub destructible_tiles: HashMap<(usize, usize), DestructibleTile>,
+    pub collision_version: u32,
+    pub width: usize,
+    pub height: usize,
+    pub boss_spawn: Option<Vec2>,
+    initial_destructible_tiles: HashMap<(usize, usize), DestructibleTile>,
+    destroyed_tiles: Vec<(String, usize, Tile)>,
     map_rect: Rect,
 }
 
 impl Map {
     pub fn new(id: Entity, settings: &GameSettings, map: TiledMap) -> Self {
         let mut tileset_collision_map = HashMap::<String, HashSet<usize>>::new();
+        let mut tileset_effect_map = HashMap::<String, HashMap<usize, TileEffect>>::new();
+        let mut tileset_destructible_map =
+            HashMap::<String, HashMap<usize, (f32, Option<TileDrop>)>>::new();
         for tileset in &map.raw_tiled_map.tilesets {
             let mut collision = HashSet::<usize>::new();
+            let mut effects = HashMap::<usize, TileEffect>::new();
+            let mut destructibles = HashMap::<usize, (f32, Option<TileDrop>)>::new();
             for tile in &tileset.tiles {
                 let has_collision = tile.properties.iter().any(|prop| prop.name == "collision");
                 if has_collision {
                     collision.insert(tile.id);
                 }
+
+                let mut effect = TileEffect::default();
+                let mut destructible_hp = None;
+                let mut drop = None;
+                for prop in &tile.properties {
+                    let value = prop.value.to_string();
+                    match prop.name.as_str() {
+                        "damage" => effect.damage = Some(value.parse().unwrap_or(1.)),
+                        "slow" => effect.slow = Some(value.parse().unwrap_or(0.5)),
+                        "aberration" => effect.aberration = Some(value.parse().unwrap_or(0.05)),
+                        "pit" => effect.pit = true,
+                        "destructible" => destructible_hp = Some(value.parse().unwrap_or(30.)),
+                        "drop" => {
+                            drop = match value.as_str() {
+                                "health" => Some(TileDrop::Health),
+                                "anomaly_small" => Some(TileDrop::AnomalySmall),
+                                "anomaly_big" => Some(TileDrop::AnomalyBig),
+                                _ => Some(TileDrop::Random),
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                if !effect.is_empty() {
+                    effects.insert(tile.id, effect);
+                }
+                if let Some(hp) = destructible_hp {
+                    destructibles.insert(tile.id, (hp, drop));
+                }
             }
             tileset_collision_map.insert(tileset.name.clone(), collision);
+            tileset_effect_map.insert(tileset.name.clone(), effects);
+            tileset_destructible_map.insert(tileset.name.clone(), destructibles);
         }
 
         let mut map_collision = HashSet::<(usize, usize)>::new();
-        for (_, layer) in &map.layers {
+        let mut map_effects = HashMap::<(usize, usize), TileEffect>::new();
+        let mut destructible_tiles = HashMap::<(usize, usize), DestructibleTile>::new();
+        for (layer_name, layer) in &map.layers {
             let layer_width = layer.width;
             for (tile_index, tile) in layer.data.iter().enumerate() {
                 if let Some(tile) = tile {
+                    let tile_x = tile_index % layer_width as usize;
+                    let tile_y = tile_index / layer_width as usize;
                     let ts = tileset_collision_map.get(&tile.tileset);
                     if let Some(ts) = ts {
                         if ts.contains(&(tile.id as usize)) {
-                            let tile_x = tile_index % layer_width as usize;
-                            let tile_y = tile_index / layer_width as usize;
                             map_collision.insert((tile_x, tile_y));
                         }
                     }
+                    let ts = tileset_effect_map.get(&tile.tileset);
+                    if let Some(effect) = ts.and_then(|ts| ts.get(&(tile.id as usize))) {
+                        map_effects
+                            .entry((tile_x, tile_y))
+                            .or_default()
+                            .merge(effect);
+                    }
+                    let ts = tileset_destructible_map.get(&tile.tileset);
+                    if let Some((hp, drop)) = ts.and_then(|ts| ts.get(&(tile.id as usize))) {
+                        let destructible = destructible_tiles.entry((tile_x, tile_y)).or_insert(
+                            DestructibleTile {
+                                hp: *hp,
+                                drop: *drop,
+                                collision: false,
+                                layers: vec![],
+                                last_hit_time: 0.,
+                            },
+                        );
+                        destructible.layers.push(layer_name.clone());
+                        destructible.collision = map_collision.contains(&(tile_x, tile_y));
+                    }
                 }
             }
         }
 
+        let width = map
+            .layers
+            .values()
+            .map(|layer| layer.width as usize)
+            .max()
+            .unwrap_or(0);
+        let height = map
+            .layers
+            .values()
+            .map(|layer| layer.data.len() / (layer.width as usize).max(1))
+            .max()
+            .unwrap_or(0);
+
+        let boss_spawn = map
+            .layers
+            .values()
+            .flat_map(|layer| layer.objects.iter())
+            .find(|object| object.properties.contains_key("boss"))
+            .map(|object| vec2(object.world_x + 4., object.world_y - 4.));
+
         Self {
             id,
             tiled_map: map,
             tileset_collision_map,
             map_collision,
+            tileset_effect_map,
+            map_effects,
+            initial_destructible_tiles: destructible_tiles.clone(),
+            destructible_tiles,
+            destroyed_tiles: vec![],
+            collision_version: 0,
+            width,
+            height,
+            boss_spawn,
             map_rect: Rect::new(0., 0., settings.resolution.x, settings.resolution.y),
         }
     }
 
+    pub fn tile_effect(&self, pos: Vec2) -> Option<&TileEffect> {
+        if pos.x < 0. || pos.y < 0. {
+            return None;
+        }
+        let tile = ((pos.x / 8.) as usize, (pos.y / 8.) as usize);
+        self.map_effects.get(&tile)
+    }
+
+    pub fn reset(&mut self) {
+        for (layer_name, index, tile) in self.destroyed_tiles.drain(..) {
+            if let Some(layer) = self.tiled_map.layers.get_mut(&layer_name) {
+                layer.data[index] = Some(tile);
+            }
+        }
+        self.destructible_tiles = self.initial_destructible_tiles.clone();
+        for (pos, destructible) in &self.destructible_tiles {
+            if destructible.collision {
+                self.map_collision.insert(*pos);
+            }
+        }
+        self.collision_version += 1;
+    }
+
+    /// Returns the destroyed tile if the damage brought its hp to zero.
+    pub fn damage_tile(&mut self, tile: (usize, usize), damage: f32) -> Option<DestructibleTile> {
+        let destructible = self.destructible_tiles.get_mut(&tile)?;
+        if get_time() - destructible.last_hit_time < 0.2 {
+            return None;
+        }
+        destructible.last_hit_time = get_time();
+        destructible.hp -= damage;
+        if destructible.hp > 0. {
+            return None;
+        }
+
+        let destructible = self.destructible_tiles.remove(&tile).unwrap();
+        self.map_collision.remove(&tile);
+        self.collision_version += 1;
+        for layer_name in &destructible.layers {
+            if let Some(layer) = self.tiled_map.layers.get_mut(layer_name) {
+                let index = tile.1 * layer.width as usize + tile.0;
+                if let Some(removed) = layer.data[index].take() {
+                    self.destroyed_tiles
+                        .push((layer_name.clone(), index, removed));
+                }
+            }
+        }
+        Some(destructible)
+    }
+
+    pub fn is_walkable(&self, x: i32, y: i32) -> bool {
+        x >= 0
+            && y >= 0
+            && (x as usize) < self.width
+            && (y as usize) < self.height
+            && !self.map_collision.contains(&(x as usize, y as usize))
+    }
+
+    /// Walks the tile grid from `from` to `to` and returns the first colliding tile.
+    pub fn raycast(&self, from: Vec2, to: Vec2) -> Option<RaycastHit> {
+        raycast_tiles(from, to, |x, y| {
+            x >= 0 && y >= 0 && self.map_collision.contains(&(x as usize, y as usize))
+        })
+    }
+
+    pub fn has_line_of_sight(&self, from: Vec2, to: Vec2) -> bool {
+        self.raycast(from, to).is_none()
+    }
+
     pub fn draw_base(&self) {
         self.tiled_map.draw_tiles("layer0", self.map_rect, None);
         self.tiled_map.draw_tiles("layer1", self.map_rect, None);
@@ -67,5 +286,118 @@ impl Map {
         for (x, y) in &self.map_collision {
             draw_rectangle_lines(*x as f32 * 8., *y as f32 * 8., 8., 8., 1., GREEN);
         }
+        for (x, y) in self.map_effects.keys() {
+            draw_rectangle_lines(*x as f32 * 8., *y as f32 * 8., 8., 8., 1., ORANGE);
+        }
+        for (x, y) in self.destructible_tiles.keys() {
+            draw_rectangle_lines(*x as f32 * 8., *y as f32 * 8., 8., 8., 1., YELLOW);
+        }
+    }
+}
+
+fn raycast_tiles(from: Vec2, to: Vec2, is_solid: impl Fn(i32, i32) -> bool) -> Option<RaycastHit> {
+    let delta = to - from;
+    let length = delta.length();
+    if length <= 0. {
+        return None;
+    }
+    let dir = delta / length;
+
+    let mut tile = ((from.x / 8.).floor() as i32, (from.y / 8.).floor() as i32);
+    let step = (
+        if dir.x < 0. { -1 } else { 1 },
+        if dir.y < 0. { -1 } else { 1 },
+    );
+    let t_delta = vec2(
+        if dir.x != 0. {
+            (8. / dir.x).abs()
+        } else {
+            f32::INFINITY
+        },
+        if dir.y != 0. {
+            (8. / dir.y).abs()
+        } else {
+            f32::INFINITY
+        },
+    );
+    let mut t_max = vec2(
+        if dir.x > 0. {
+            ((tile.0 + 1) as f32 * 8. - from.x) / dir.x
+        } else if dir.x < 0. {
+            (tile.0 as f32 * 8. - from.x) / dir.x
+        } else {
+            f32::INFINITY
+        },
+        if dir.y > 0. {
+            ((tile.1 + 1) as f32 * 8. - from.y) / dir.y
+        } else if dir.y < 0. {
+            (tile.1 as f32 * 8. - from.y) / dir.y
+        } else {
+            f32::INFINITY
+        },
+    );
+
+    let mut t = 0.;
+    loop {
+        if is_solid(tile.0, tile.1) {
+            return Some(RaycastHit {
+                tile: (tile.0 as usize, tile.1 as usize),
+                point: from + dir * t,
+                distance: t,
+            });
+        }
+
+        if t_max.x < t_max.y {
+            t = t_max.x;
+            t_max.x += t_delta.x;
+            tile.0 += step.0;
+        } else {
+            t = t_max.y;
+            t_max.y += t_delta.y;
+            tile.1 += step.1;
+        }
+        if t > length {
+            return None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::test_grid::{center, solid};
+
+    #[test]
+    fn open_corridor_has_line_of_sight() {
+        let solid = solid(&["#####", ".....", "#####"]);
+        assert!(raycast_tiles(center(0, 1), center(4, 1), &solid).is_none());
+    }
+
+    #[test]
+    fn blocked_corridor_hits_the_wall() {
+        let solid = solid(&["#####", "..#..", "#####"]);
+        let hit = raycast_tiles(center(0, 1), center(4, 1), &solid).unwrap();
+        assert_eq!(hit.tile, (2, 1));
+        assert_eq!(hit.point, vec2(16., 12.));
+        assert_eq!(hit.distance, 12.);
+    }
+
+    #[test]
+    fn ray_stops_before_reaching_the_wall() {
+        let solid = solid(&["#####", "....#", "#####"]);
+        assert!(raycast_tiles(center(0, 1), center(3, 1), &solid).is_none());
+    }
+
+    #[test]
+    fn diagonal_corner_blocks_sight() {
+        let solid = solid(&[".#", "#."]);
+        assert!(raycast_tiles(center(0, 0), center(1, 1), &solid).is_some());
+        assert!(raycast_tiles(center(1, 1), center(0, 0), &solid).is_some());
+    }
+
+    #[test]
+    fn same_start_and_end_never_hits() {
+        let solid = solid(&["#"]);
+        assert!(raycast_tiles(center(0, 0), center(0, 0), &solid).is_none());
     }
 }
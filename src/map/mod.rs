@@ -1 +1,29 @@
 pub mod map;
+pub mod navigation;
+
+#[cfg(test)]
+mod test_grid {
+    use macroquad::math::{vec2, Vec2};
+
+    /// Hand-built collision grid: `.` is walkable, anything else and everything outside the
+    /// rows is solid.
+    pub fn walkable(rows: &'static [&'static str]) -> impl Fn(i32, i32) -> bool {
+        move |x, y| {
+            y >= 0
+                && x >= 0
+                && rows
+                    .get(y as usize)
+                    .and_then(|row| row.as_bytes().get(x as usize))
+                    == Some(&b'.')
+        }
+    }
+
+    pub fn solid(rows: &'static [&'static str]) -> impl Fn(i32, i32) -> bool {
+        let walkable = walkable(rows);
+        move |x, y| !walkable(x, y)
+    }
+
+    pub fn center(x: i32, y: i32) -> Vec2 {
+        vec2(x as f32 * 8. + 4., y as f32 * 8. + 4.)
+    }
+}
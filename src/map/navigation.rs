@@ -0,0 +1,197 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+use macroquad::prelude::*;
+
+use crate::entity::entity_id::Entity;
+
+use super::map::Map;
+
+const NEIGHBOURS: [(i32, i32, u32); 8] = [
+    (-1, 0, 10),
+    (1, 0, 10),
+    (0, -1, 10),
+    (0, 1, 10),
+    (-1, -1, 14),
+    (1, -1, 14),
+    (-1, 1, 14),
+    (1, 1, 14),
+];
+
+/// Flow field towards a target tile, built from the map collision grid.
+pub struct NavGrid {
+    distances: HashMap<(i32, i32), u32>,
+    target_tile: Option<(i32, i32)>,
+    map_id: Option<Entity>,
+    collision_version: u32,
+}
+
+impl NavGrid {
+    pub fn new() -> Self {
+        Self {
+            distances: HashMap::new(),
+            target_tile: None,
+            map_id: None,
+            collision_version: 0,
+        }
+    }
+
+    pub fn update(&mut self, map: &Map, target: Vec2) {
+        let target_tile = tile_of(target);
+        if self.target_tile == Some(target_tile)
+            && self.map_id == Some(map.id)
+            && self.collision_version == map.collision_version
+        {
+            return;
+        }
+        self.target_tile = Some(target_tile);
+        self.map_id = Some(map.id);
+        self.collision_version = map.collision_version;
+        self.flood(target_tile, |x, y| map.is_walkable(x, y));
+    }
+
+    /// Direction to move in from `from` to follow the flow field towards `target`.
+    pub fn direction(&self, map: &Map, from: Vec2, target: Vec2) -> Vec2 {
+        self.direction_on(from, target, |x, y| map.is_walkable(x, y))
+    }
+
+    fn flood(&mut self, target_tile: (i32, i32), is_walkable: impl Fn(i32, i32) -> bool) {
+        self.distances.clear();
+        let mut queue = BinaryHeap::new();
+        self.distances.insert(target_tile, 0);
+        queue.push(Reverse((0, target_tile)));
+
+        while let Some(Reverse((distance, tile))) = queue.pop() {
+            if self.distances.get(&tile).is_some_and(|d| *d < distance) {
+                continue;
+            }
+            for (dx, dy, cost) in NEIGHBOURS {
+                let next = (tile.0 + dx, tile.1 + dy);
+                if !can_step(&is_walkable, tile, next) {
+                    continue;
+                }
+                let next_distance = distance + cost;
+                if self
+                    .distances
+                    .get(&next)
+                    .map_or(true, |d| next_distance < *d)
+                {
+                    self.distances.insert(next, next_distance);
+                    queue.push(Reverse((next_distance, next)));
+                }
+            }
+        }
+    }
+
+    fn direction_on(
+        &self,
+        from: Vec2,
+        target: Vec2,
+        is_walkable: impl Fn(i32, i32) -> bool,
+    ) -> Vec2 {
+        let straight = (target - from).normalize_or_zero();
+        let tile = tile_of(from);
+        let distance = match self.distances.get(&tile) {
+            Some(distance) if *distance > 0 => *distance,
+            _ => return straight,
+        };
+
+        let mut best = None;
+        let mut best_distance = distance;
+        for (dx, dy, _) in NEIGHBOURS {
+            let next = (tile.0 + dx, tile.1 + dy);
+            if !can_step(&is_walkable, tile, next) {
+                continue;
+            }
+            if let Some(next_distance) = self.distances.get(&next) {
+                if *next_distance < best_distance {
+                    best_distance = *next_distance;
+                    best = Some(next);
+                }
+            }
+        }
+
+        match best {
+            Some(next) => {
+                let center = vec2(next.0 as f32 * 8. + 4., next.1 as f32 * 8. + 4.);
+                (center - from).normalize_or_zero()
+            }
+            None => straight,
+        }
+    }
+}
+
+fn tile_of(pos: Vec2) -> (i32, i32) {
+    ((pos.x / 8.).floor() as i32, (pos.y / 8.).floor() as i32)
+}
+
+fn can_step(is_walkable: &impl Fn(i32, i32) -> bool, from: (i32, i32), to: (i32, i32)) -> bool {
+    if !is_walkable(to.0, to.1) {
+        return false;
+    }
+    // No corner cutting for diagonal steps
+    if from.0 != to.0 && from.1 != to.1 {
+        return is_walkable(from.0, to.1) && is_walkable(to.0, from.1);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::test_grid::{center, walkable};
+
+    #[test]
+    fn blocked_corridor_falls_back_to_straight_line() {
+        let walkable = walkable(&["..#.."]);
+        let mut nav = NavGrid::new();
+        nav.flood((4, 0), &walkable);
+
+        assert!(!nav.distances.contains_key(&(0, 0)));
+        assert_eq!(
+            nav.direction_on(center(0, 0), center(4, 0), &walkable),
+            vec2(1., 0.)
+        );
+    }
+
+    #[test]
+    fn walks_around_a_wall() {
+        let walkable = walkable(&["..#..", "....."]);
+        let mut nav = NavGrid::new();
+        nav.flood((4, 0), &walkable);
+
+        assert_eq!(nav.distances[&(2, 1)], 24);
+        assert_eq!(
+            nav.direction_on(center(1, 0), center(4, 0), &walkable),
+            vec2(0., 1.)
+        );
+    }
+
+    #[test]
+    fn diagonal_steps_dont_cut_corners() {
+        let walkable = walkable(&[".#", ".."]);
+        let mut nav = NavGrid::new();
+        nav.flood((1, 1), &walkable);
+
+        assert_eq!(nav.distances[&(0, 0)], 20);
+        assert_eq!(
+            nav.direction_on(center(0, 0), center(1, 1), &walkable),
+            vec2(0., 1.)
+        );
+    }
+
+    #[test]
+    fn start_on_target_stands_still() {
+        let walkable = walkable(&["..."]);
+        let mut nav = NavGrid::new();
+        nav.flood((1, 0), &walkable);
+
+        assert_eq!(nav.distances[&(1, 0)], 0);
+        assert_eq!(
+            nav.direction_on(center(1, 0), center(1, 0), &walkable),
+            Vec2::ZERO
+        );
+    }
+}
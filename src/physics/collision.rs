@@ -14,6 +14,7 @@ pub struct Collision {
     pub point: Vec2,
     pub overlap: f32,
     pub normal: Vec2,
+    pub tile: Option<(usize, usize)>,
 }
 
 pub fn check_collision_circles(
@@ -33,6 +34,7 @@ pub fn check_collision_circles(
             point,
             normal,
             overlap,
+            tile: None,
         });
     }
 
@@ -152,6 +154,7 @@ pub fn resolve_map_collision(
                         normal: Vec2::ZERO,
                         overlap: 0.,
                         point: closest_point,
+                        tile: Some((*x, *y)),
                     },
                 );
 
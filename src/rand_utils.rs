@@ -6,3 +6,64 @@ use macroquad::{
 pub fn rand_dir() -> Vec2 {
     vec2(rand::gen_range(-1., 1.), rand::gen_range(-1., 1.)).normalize()
 }
+
+/// Small xorshift generator for reproducible results from a seed.
+pub struct SeededRng(u64);
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn gen_range(&mut self, low: usize, high: usize) -> usize {
+        low + (self.next_u64() % (high - low) as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = SeededRng::new(7);
+        let mut b = SeededRng::new(7);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn different_seeds_differ() {
+        let mut a = SeededRng::new(1);
+        let mut b = SeededRng::new(2);
+        assert!((0..10).any(|_| a.next_u64() != b.next_u64()));
+    }
+
+    #[test]
+    fn zero_seed_does_not_get_stuck() {
+        let mut rng = SeededRng::new(0);
+        assert!((0..10).all(|_| rng.next_u64() != 0));
+    }
+
+    #[test]
+    fn values_stay_in_range() {
+        let mut rng = SeededRng::new(123);
+        for _ in 0..1000 {
+            let value = rng.next_f32();
+            assert!((0. ..1.).contains(&value));
+            assert!((3..8).contains(&rng.gen_range(3, 8)));
+        }
+    }
+}
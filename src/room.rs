@@ -1,18 +1,33 @@
-use macroquad::rand;
-
-use crate::entity::{
-    entities::Ecs,
-    upgrades::{Upgrade, Upgrades},
+use crate::{
+    entity::{
+        entities::Ecs,
+        upgrades::{Upgrade, Upgrades},
+    },
+    timer::Timer,
 };
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Enemy {
     Hopper,
     Spitter,
     Stomper,
+    Burrower,
     Mirituhg,
 }
 
+impl Enemy {
+    pub fn from_name(name: &str) -> Option<Enemy> {
+        match name {
+            "hopper" => Some(Enemy::Hopper),
+            "spitter" => Some(Enemy::Spitter),
+            "stomper" => Some(Enemy::Stomper),
+            "burrower" => Some(Enemy::Burrower),
+            "mirituhg" => Some(Enemy::Mirituhg),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum Item {
     Health,
@@ -20,6 +35,14 @@ pub enum Item {
     AberrationRelief,
 }
 
+#[derive(Clone)]
+pub struct WaveConfig {
+    pub wave_size: usize,
+    pub kills_to_advance: usize,
+    pub wave_time: f32,
+    pub spawn_warning_time: f32,
+}
+
 #[derive(Clone)]
 pub struct Room {
     pub map_index: usize,
@@ -31,29 +54,18 @@ pub struct Room {
     pub available_upgrades: Vec<Upgrade>,
     pub upgrade_chosen: bool,
     pub entities_spawned: bool,
+    pub wave_config: WaveConfig,
+    pub wave_timer: Timer,
+    pub waves_spawned: usize,
+    pub kills_in_wave: usize,
+    pub boss: bool,
+    pub locked: bool,
+    pub elite_chance: f32,
+    pub elite_affixes: usize,
 }
 
 impl Room {
-    pub fn new(map_index: usize, difficulty: f32) -> Room {
-        let mut remaining_difficulty = difficulty;
-        let enemy_values = vec![
-            (Enemy::Hopper, 1.),
-            (Enemy::Spitter, 2.),
-            (Enemy::Stomper, 5.),
-        ];
-
-        let mut enemies = vec![];
-
-        while remaining_difficulty > 1. {
-            let rand_index = rand::gen_range(0, enemy_values.len());
-            let enemy = enemy_values[rand_index];
-            if enemy.1 > remaining_difficulty {
-                continue;
-            }
-            enemies.push(enemy.0);
-            remaining_difficulty -= enemy.1;
-        }
-
+    pub fn new(map_index: usize, enemies: Vec<Enemy>, wave_config: WaveConfig) -> Room {
         Room {
             map_index,
             enemies_to_spawn: enemies,
@@ -64,14 +76,29 @@ impl Room {
             available_upgrades: Upgrades::weapon_selection(),
             upgrade_chosen: false,
             entities_spawned: false,
+            wave_timer: Timer::new(wave_config.wave_time, false),
+            wave_config,
+            waves_spawned: 0,
+            kills_in_wave: 0,
+            boss: false,
+            locked: false,
+            elite_chance: 0.,
+            elite_affixes: 0,
         }
     }
 
+    pub fn new_boss(map_index: usize, wave_config: WaveConfig) -> Room {
+        let mut room = Room::new(map_index, vec![], wave_config);
+        room.boss = true;
+        room
+    }
+
     pub fn check_completed(&mut self, ecs: &Ecs) {
         let enemy_entities = ecs.check_components(|e, comps| comps.enemies.contains_key(e));
         self.completed = self.started
             && self.entities_spawned
             && self.upgrade_chosen
+            && !self.locked
             && enemy_entities.len() == 0;
     }
 
@@ -38,12 +38,69 @@ impl WindowSize {
     }
 }
 
+#[derive(Default, PartialEq, Clone, Copy)]
+pub enum FireMode {
+    #[default]
+    Auto,
+    Hold,
+    Toggle,
+}
+
+impl FireMode {
+    pub fn text(&self) -> String {
+        match self {
+            FireMode::Auto => "Auto",
+            FireMode::Hold => "Hold",
+            FireMode::Toggle => "Toggle",
+        }
+        .to_string()
+    }
+
+    pub fn list() -> Vec<FireMode> {
+        vec![FireMode::Auto, FireMode::Hold, FireMode::Toggle]
+    }
+}
+
+#[derive(Default, PartialEq, Clone, Copy)]
+pub enum AimAssist {
+    Off,
+    #[default]
+    Normal,
+    Strong,
+}
+
+impl AimAssist {
+    pub fn text(&self) -> String {
+        match self {
+            AimAssist::Off => "Off",
+            AimAssist::Normal => "Normal",
+            AimAssist::Strong => "Strong",
+        }
+        .to_string()
+    }
+
+    /// Scales the auto-aim cone.
+    pub fn strength(&self) -> f32 {
+        match self {
+            AimAssist::Off => 0.,
+            AimAssist::Normal => 1.,
+            AimAssist::Strong => 2.,
+        }
+    }
+
+    pub fn list() -> Vec<AimAssist> {
+        vec![AimAssist::Off, AimAssist::Normal, AimAssist::Strong]
+    }
+}
+
 pub struct GameSettings {
     pub sfx_volume_lin: f32,
     pub sfx_volume: f32,
     pub music_volume_lin: f32,
     pub music_volume: f32,
     pub window_size: WindowSize,
+    pub fire_mode: FireMode,
+    pub aim_assist: AimAssist,
     pub resolution: Vec2,
 }
 
@@ -55,6 +112,8 @@ impl Default for GameSettings {
             music_volume_lin: Default::default(),
             music_volume: Default::default(),
             window_size: Default::default(),
+            fire_mode: Default::default(),
+            aim_assist: Default::default(),
             resolution: vec2(360., 240.),
         };
         settings.set_music_volume_lin(0.60);
@@ -31,6 +31,18 @@ impl IndexedSprite {
         self.draw_with_dest(data, pos, index, None, flipped);
     }
 
+    /// Draws the frame scaled around the sprite's position offset.
+    pub fn draw_scaled(&self, data: &GameData, pos: Vec2, index: usize, scale: f32, flipped: bool) {
+        let source = self.texture_source(data, index);
+        self.draw_with_dest(
+            data,
+            pos - self.position_offset * (scale - 1.),
+            index,
+            Some(source.size() * scale),
+            flipped,
+        );
+    }
+
     pub fn draw_with_dest(
         &self,
         data: &GameData,
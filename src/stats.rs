@@ -0,0 +1,275 @@
+use std::{collections::HashMap, fmt};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Stat {
+    MaxHp,
+    MoveSpeed,
+    CritChance,
+    CritMultiplier,
+    Damage,
+    Cooldown,
+    Amount,
+    Range,
+    RotationSpeed,
+    Cooling,
+    Pierce,
+    Bounces,
+    Burn,
+    Poison,
+    Slow,
+    Stun,
+}
+
+impl Stat {
+    pub const ALL: [Stat; 16] = [
+        Stat::MaxHp,
+        Stat::MoveSpeed,
+        Stat::CritChance,
+        Stat::CritMultiplier,
+        Stat::Damage,
+        Stat::Cooldown,
+        Stat::Amount,
+        Stat::Range,
+        Stat::RotationSpeed,
+        Stat::Cooling,
+        Stat::Pierce,
+        Stat::Bounces,
+        Stat::Burn,
+        Stat::Poison,
+        Stat::Slow,
+        Stat::Stun,
+    ];
+
+    fn clamp(&self, value: f32) -> f32 {
+        match self {
+            Stat::CritChance => value.clamp(0., 1.),
+            _ => value.max(0.),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ModifierSource {
+    Upgrade,
+    Status,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ModifierKind {
+    /// Added to the base value.
+    Flat(f32),
+    /// Summed with the other percentages of the stat, so two +20% make +40%.
+    Percent(f32),
+    /// Applied after everything else, one after another.
+    Multiply(f32),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct StatModifier {
+    pub stat: Stat,
+    pub kind: ModifierKind,
+    pub source: ModifierSource,
+}
+
+impl StatModifier {
+    pub fn flat(stat: Stat, value: f32) -> Self {
+        Self {
+            stat,
+            kind: ModifierKind::Flat(value),
+            source: ModifierSource::Upgrade,
+        }
+    }
+
+    pub fn percent(stat: Stat, value: f32) -> Self {
+        Self {
+            stat,
+            kind: ModifierKind::Percent(value),
+            source: ModifierSource::Upgrade,
+        }
+    }
+
+    pub fn multiply(stat: Stat, value: f32) -> Self {
+        Self {
+            stat,
+            kind: ModifierKind::Multiply(value),
+            source: ModifierSource::Upgrade,
+        }
+    }
+
+    pub fn from_source(mut self, source: ModifierSource) -> Self {
+        self.source = source;
+        self
+    }
+}
+
+/// Base values plus modifiers. Final values are cached and only recomputed when modifiers change.
+#[derive(Clone, Default)]
+pub struct Stats {
+    base: HashMap<Stat, f32>,
+    modifiers: Vec<StatModifier>,
+    cache: HashMap<Stat, f32>,
+}
+
+impl Stats {
+    pub fn new(base: &[(Stat, f32)]) -> Self {
+        let mut stats = Self {
+            base: base.iter().copied().collect(),
+            ..Default::default()
+        };
+        stats.refresh();
+        stats
+    }
+
+    pub fn base(&self, stat: Stat) -> f32 {
+        self.base.get(&stat).copied().unwrap_or(0.)
+    }
+
+    pub fn get(&self, stat: Stat) -> f32 {
+        self.cache.get(&stat).copied().unwrap_or(0.)
+    }
+
+    pub fn add(&mut self, modifiers: impl IntoIterator<Item = StatModifier>) {
+        self.modifiers.extend(modifiers);
+        self.refresh();
+    }
+
+    /// Replaces every modifier coming from `source`. Does nothing if they're unchanged.
+    pub fn set_source(&mut self, source: ModifierSource, modifiers: Vec<StatModifier>) {
+        let current = self
+            .modifiers
+            .iter()
+            .filter(|modifier| modifier.source == source)
+            .copied()
+            .collect::<Vec<_>>();
+        if current == modifiers {
+            return;
+        }
+        self.modifiers.retain(|modifier| modifier.source != source);
+        self.add(modifiers);
+    }
+
+    /// Breaks down how the final value of `stat` was computed.
+    pub fn explain(&self, stat: Stat) -> StatExplanation {
+        StatExplanation {
+            stat,
+            base: self.base(stat),
+            modifiers: self
+                .modifiers
+                .iter()
+                .filter(|modifier| modifier.stat == stat)
+                .copied()
+                .collect(),
+            value: self.get(stat),
+        }
+    }
+
+    fn refresh(&mut self) {
+        self.cache = Stat::ALL
+            .iter()
+            .map(|stat| (*stat, self.compute(*stat)))
+            .collect();
+    }
+
+    fn compute(&self, stat: Stat) -> f32 {
+        let mut flat = self.base(stat);
+        let mut percent = 0.;
+        let mut multiplier = 1.;
+        for modifier in self.modifiers.iter().filter(|m| m.stat == stat) {
+            match modifier.kind {
+                ModifierKind::Flat(value) => flat += value,
+                ModifierKind::Percent(value) => percent += value,
+                ModifierKind::Multiply(value) => multiplier *= value,
+            }
+        }
+        stat.clamp(flat * (1. + percent) * multiplier)
+    }
+}
+
+pub struct StatExplanation {
+    pub stat: Stat,
+    pub base: f32,
+    pub modifiers: Vec<StatModifier>,
+    pub value: f32,
+}
+
+impl fmt::Display for StatExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.stat, self.base)?;
+        for modifier in &self.modifiers {
+            match modifier.kind {
+                ModifierKind::Flat(value) => write!(f, " {:+}", value)?,
+                ModifierKind::Percent(value) => write!(f, " {:+}%", value * 100.)?,
+                ModifierKind::Multiply(value) => write!(f, " x{}", value)?,
+            }
+            write!(f, " ({:?})", modifier.source)?;
+        }
+        write!(f, " = {}", self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_then_percent_then_multiply() {
+        let mut stats = Stats::new(&[(Stat::Damage, 10.)]);
+        stats.add([
+            StatModifier::multiply(Stat::Damage, 2.),
+            StatModifier::percent(Stat::Damage, 0.2),
+            StatModifier::flat(Stat::Damage, 5.),
+            StatModifier::percent(Stat::Damage, 0.3),
+        ]);
+        // (10 + 5) * (1 + 0.2 + 0.3) * 2
+        assert_eq!(stats.get(Stat::Damage), 45.);
+    }
+
+    #[test]
+    fn multipliers_stack_multiplicatively() {
+        let mut stats = Stats::new(&[(Stat::Cooldown, 1.)]);
+        stats.add([
+            StatModifier::multiply(Stat::Cooldown, 0.5),
+            StatModifier::multiply(Stat::Cooldown, 0.5),
+        ]);
+        assert_eq!(stats.get(Stat::Cooldown), 0.25);
+    }
+
+    #[test]
+    fn crit_chance_is_clamped() {
+        let mut stats = Stats::new(&[(Stat::CritChance, 0.5)]);
+        stats.add([StatModifier::flat(Stat::CritChance, 0.8)]);
+        assert_eq!(stats.get(Stat::CritChance), 1.);
+
+        let mut stats = Stats::new(&[(Stat::CritChance, 0.5)]);
+        stats.add([StatModifier::flat(Stat::CritChance, -0.8)]);
+        assert_eq!(stats.get(Stat::CritChance), 0.);
+    }
+
+    #[test]
+    fn other_stats_never_go_negative() {
+        let mut stats = Stats::new(&[(Stat::MoveSpeed, 72.)]);
+        stats.add([StatModifier::percent(Stat::MoveSpeed, -1.5)]);
+        assert_eq!(stats.get(Stat::MoveSpeed), 0.);
+    }
+
+    #[test]
+    fn set_source_only_replaces_its_own_source() {
+        let mut stats = Stats::new(&[(Stat::MoveSpeed, 100.)]);
+        stats.add([StatModifier::flat(Stat::MoveSpeed, 20.)]);
+
+        let slow = StatModifier::multiply(Stat::MoveSpeed, 0.5).from_source(ModifierSource::Status);
+        stats.set_source(ModifierSource::Status, vec![slow]);
+        assert_eq!(stats.get(Stat::MoveSpeed), 60.);
+
+        stats.set_source(ModifierSource::Status, vec![]);
+        assert_eq!(stats.get(Stat::MoveSpeed), 120.);
+        assert_eq!(stats.explain(Stat::MoveSpeed).modifiers.len(), 1);
+    }
+
+    #[test]
+    fn unmodified_stats_use_base() {
+        let stats = Stats::new(&[(Stat::MaxHp, 3.)]);
+        assert_eq!(stats.get(Stat::MaxHp), 3.);
+        assert_eq!(stats.get(Stat::Pierce), 0.);
+    }
+}
@@ -0,0 +1,356 @@
+use macroquad::{prelude::*, rand::gen_range};
+
+use crate::{
+    entity::{
+        ai::{AiAction, AiMachine, Condition, Movement},
+        animated_sprite::AnimatedSprite,
+        burrower::spawn_burrower,
+        damage::Damage,
+        emitter::BulletEmitter,
+        entities::{ComponentColl, Ecs},
+        entity_id::Entity,
+        events::DamageEvent,
+        hopper::spawn_hopper,
+        impact::spawn_dust,
+        spitter::spawn_spitter,
+        stomper::spawn_stomper,
+    },
+    game_data::GameData,
+    rand_utils::rand_dir,
+    room::Enemy,
+};
+
+use super::steering::{steering_velocity, SteeringInput};
+
+struct AiContext<'a> {
+    entity: Entity,
+    position: Vec2,
+    player_pos: Vec2,
+    players: &'a Vec<Entity>,
+    neighbors: &'a Vec<(Entity, Vec2)>,
+    hp_fraction: f32,
+}
+
+pub fn update_ai(data: &mut GameData, ecs: &mut Ecs, damage_events: &mut Vec<DamageEvent>) {
+    let machines = ecs.check_components(|e, comps| {
+        comps.ai_machines.contains_key(e)
+            && comps.positions.contains_key(e)
+            && comps.velocities.contains_key(e)
+            && comps.animated_sprites.contains_key(e)
+    });
+
+    let players = ecs.check_components(|e, comps| {
+        comps.player_data.contains_key(e) && comps.positions.contains_key(e)
+    });
+    let Some(player_pos) = players
+        .first()
+        .map(|player_e| *ecs.components.positions.get(player_e).unwrap())
+    else {
+        return;
+    };
+
+    let neighbors = machines
+        .iter()
+        .map(|e| (*e, *ecs.components.positions.get(e).unwrap()))
+        .collect::<Vec<_>>();
+
+    let mut spawns = vec![];
+    for entity in &machines {
+        if ecs
+            .components
+            .status_effects
+            .get(entity)
+            .map_or(false, |status| status.disables_ai())
+        {
+            continue;
+        }
+
+        let machine = ecs.components.ai_machines.get_mut(entity).unwrap();
+        let position = *ecs.components.positions.get(entity).unwrap();
+        let velocity = ecs.components.velocities.get_mut(entity).unwrap();
+        let sprite = ecs.components.animated_sprites.get_mut(entity).unwrap();
+        let emitters = &mut ecs.components.bullet_emitters;
+
+        let hp = ecs.components.health.get(entity).map_or(1., |h| h.hp);
+        let max_hp = *machine.max_hp.get_or_insert(hp);
+        let ctx = AiContext {
+            entity: *entity,
+            position,
+            player_pos,
+            players: &players,
+            neighbors: &neighbors,
+            hp_fraction: hp / max_hp.max(1.),
+        };
+
+        let behavior = machine.behavior.clone();
+        let mut just_entered = false;
+        if machine.entering {
+            enter_state(machine, sprite);
+            for action in &behavior.states[machine.current].on_enter {
+                run_action(
+                    action,
+                    &ctx,
+                    machine,
+                    data,
+                    emitters,
+                    damage_events,
+                    &mut spawns,
+                );
+            }
+            just_entered = true;
+        }
+
+        let dt = get_frame_time();
+        let before = if just_entered {
+            -1.
+        } else {
+            machine.time_in_state
+        };
+        machine.time_in_state += dt;
+        machine.elapsed += dt;
+
+        let state = &behavior.states[machine.current];
+        for event in &state.events {
+            let fires = match event.every {
+                Some(interval) => {
+                    let count = |t: f32| {
+                        if t < event.at {
+                            -1
+                        } else {
+                            ((t - event.at) / interval) as i32
+                        }
+                    };
+                    count(machine.time_in_state) > count(before)
+                }
+                None => before < event.at && machine.time_in_state >= event.at,
+            };
+            if fires {
+                run_action(
+                    &event.action,
+                    &ctx,
+                    machine,
+                    data,
+                    emitters,
+                    damage_events,
+                    &mut spawns,
+                );
+            }
+        }
+
+        let vel = movement_velocity(&state.movement, &ctx, machine, data, *velocity, dt);
+        if !vel.is_nan() {
+            *velocity = vel;
+        }
+
+        let mut next_state = None;
+        for transition in &state.transitions {
+            if check_condition(&transition.condition, &ctx, machine, sprite, data) {
+                next_state = Some(transition.targets[gen_range(0, transition.targets.len())]);
+                break;
+            }
+        }
+
+        if let Some(next_state) = next_state {
+            for action in &state.on_exit {
+                run_action(
+                    action,
+                    &ctx,
+                    machine,
+                    data,
+                    emitters,
+                    damage_events,
+                    &mut spawns,
+                );
+            }
+            machine.current = behavior.state_index(next_state);
+            enter_state(machine, sprite);
+            for action in &behavior.states[machine.current].on_enter {
+                run_action(
+                    action,
+                    &ctx,
+                    machine,
+                    data,
+                    emitters,
+                    damage_events,
+                    &mut spawns,
+                );
+            }
+        }
+    }
+
+    for (pos, enemy) in spawns {
+        match enemy {
+            Enemy::Hopper => spawn_hopper(data, pos, ecs),
+            Enemy::Spitter => spawn_spitter(data, pos, ecs),
+            Enemy::Stomper => spawn_stomper(data, pos, ecs),
+            Enemy::Burrower => spawn_burrower(data, pos, ecs),
+            Enemy::Mirituhg => continue,
+        };
+        spawn_dust(data, ecs, pos);
+    }
+}
+
+fn enter_state(machine: &mut AiMachine, sprite: &mut AnimatedSprite) {
+    machine.entering = false;
+    machine.time_in_state = 0.;
+    machine.wander_target = None;
+    let name = machine.state().name;
+    machine.entered_at.insert(name, machine.elapsed);
+    machine.state_duration = match machine.state().duration {
+        Some((min, max)) => gen_range(min, max),
+        None => 0.,
+    };
+    if let Some(animation) = machine.state().animation {
+        if sprite.current_animation != animation || !sprite.current_animation().1.repeat {
+            sprite.set_animation(animation);
+        }
+    }
+}
+
+fn check_condition(
+    condition: &Condition,
+    ctx: &AiContext,
+    machine: &AiMachine,
+    sprite: &AnimatedSprite,
+    data: &GameData,
+) -> bool {
+    match condition {
+        Condition::Always => true,
+        Condition::TimeInState(time) => machine.time_in_state >= *time,
+        Condition::StateTimeout => machine.time_in_state >= machine.state_duration,
+        Condition::AnimationFinished => sprite.current_animation().1.completed,
+        Condition::PlayerWithin(range) => (ctx.player_pos - ctx.position).length() < *range,
+        Condition::HpBelow(fraction) => ctx.hp_fraction < *fraction,
+        Condition::LineOfSight(visible) => {
+            data.current_map()
+                .has_line_of_sight(ctx.position + vec2(0., -5.), ctx.player_pos)
+                == *visible
+        }
+        Condition::NearAnchor(range) => (machine.anchor - ctx.position).length() < *range,
+        Condition::NearLockedTarget(range) => {
+            (machine.locked_target - ctx.position).length() < *range
+        }
+        Condition::EnteredMoreRecently(first, second) => {
+            let entered = |name| machine.entered_at.get(name).copied().unwrap_or(f32::MIN);
+            entered(first) > entered(second)
+        }
+        Condition::SinceEntered(name, time) => {
+            machine.elapsed - machine.entered_at.get(name).copied().unwrap_or(0.) >= *time
+        }
+        Condition::All(conditions) => conditions
+            .iter()
+            .all(|condition| check_condition(condition, ctx, machine, sprite, data)),
+    }
+}
+
+fn movement_velocity(
+    movement: &Movement,
+    ctx: &AiContext,
+    machine: &mut AiMachine,
+    data: &GameData,
+    velocity: Vec2,
+    dt: f32,
+) -> Vec2 {
+    let towards = |target: Vec2, speed: f32| {
+        let to_target = target - ctx.position;
+        if to_target.length_squared() > 1. {
+            to_target.normalize() * speed
+        } else {
+            Vec2::ZERO
+        }
+    };
+
+    match movement {
+        Movement::Stand => Vec2::ZERO,
+        Movement::Navigate(speed) => {
+            data.navigation
+                .direction(data.current_map(), ctx.position, ctx.player_pos)
+                * *speed
+        }
+        Movement::KeepHeading(speed) => velocity.normalize_or_zero() * *speed,
+        Movement::MoveToAnchor(speed) => towards(machine.anchor, *speed),
+        Movement::MoveToLockedTarget(speed) => towards(machine.locked_target, *speed),
+        Movement::Wander {
+            speed,
+            min_radius,
+            max_radius,
+            retarget_time,
+        } => {
+            machine.wander_time += dt;
+            let reached = machine
+                .wander_target
+                .map_or(true, |target| (target - ctx.position).length_squared() < 4.);
+            if reached || machine.wander_time >= *retarget_time {
+                machine.wander_time = 0.;
+                machine.wander_target =
+                    Some(machine.anchor + rand_dir() * gen_range(*min_radius, *max_radius));
+            }
+            towards(machine.wander_target.unwrap(), *speed)
+        }
+        Movement::Steer { speed, behaviors } => {
+            let input = SteeringInput {
+                entity: ctx.entity,
+                position: ctx.position,
+                player_pos: ctx.player_pos,
+                seek_dir: data.navigation.direction(
+                    data.current_map(),
+                    ctx.position,
+                    ctx.player_pos,
+                ),
+                neighbors: ctx.neighbors,
+                orbit_sign: machine.orbit_sign,
+            };
+            steering_velocity(behaviors, *speed, &input)
+        }
+    }
+}
+
+fn run_action(
+    action: &AiAction,
+    ctx: &AiContext,
+    machine: &mut AiMachine,
+    data: &mut GameData,
+    emitters: &mut ComponentColl<BulletEmitter>,
+    damage_events: &mut Vec<DamageEvent>,
+    spawns: &mut Vec<(Vec2, Enemy)>,
+) {
+    match action {
+        AiAction::Emit {
+            pattern,
+            offset,
+            spin,
+        } => {
+            emitters.insert(
+                ctx.entity,
+                BulletEmitter::new(
+                    data.bullet_patterns.get(pattern),
+                    machine.elapsed * spin,
+                    *offset,
+                ),
+            );
+        }
+        AiAction::DamagePlayerWithin {
+            range,
+            damage,
+            knockback,
+        } => {
+            if (ctx.player_pos - ctx.position).length() < *range {
+                for player_e in ctx.players {
+                    damage_events.push(DamageEvent {
+                        source: ctx.entity,
+                        target: *player_e,
+                        damage: Damage::physical(*damage),
+                        statuses: vec![],
+                        impulse: Some(
+                            (ctx.player_pos - ctx.position).normalize_or_zero() * *knockback,
+                        ),
+                        continuous: false,
+                    });
+                }
+            }
+        }
+        AiAction::SpawnEnemy(enemy, offset) => spawns.push((machine.anchor + *offset, *enemy)),
+        AiAction::Shake(duration, distance) => data.screen_shake.shake(*duration, *distance),
+        AiAction::LockTarget => machine.locked_target = ctx.player_pos,
+    }
+}
@@ -2,7 +2,7 @@ use macroquad::prelude::*;
 
 use crate::{entity::entities::Ecs, game_data::GameData};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum ColliderType {
     Projectile,
     PlayerProjectile,
@@ -10,6 +10,8 @@ pub enum ColliderType {
     Pickup,
     Player,
     Enemy,
+    /// Underground enemies only collide with walls.
+    Burrowed,
     Map,
 }
 
@@ -51,6 +53,10 @@ impl ColliderType {
                 | ColliderType::Map => true,
                 _ => false,
             },
+            ColliderType::Burrowed => match other {
+                ColliderType::Map => true,
+                _ => false,
+            },
             ColliderType::Map => match other {
                 ColliderType::Enemy | ColliderType::Player | ColliderType::Projectile => true,
                 _ => false,
@@ -0,0 +1,92 @@
+use macroquad::prelude::*;
+
+use crate::{
+    entity::{damage_number::spawn_damage_number, entities::Ecs},
+    game_data::GameData,
+};
+
+/// Numbers younger than this absorb further hits on the same target, so damage over time
+/// shows up as one growing number.
+const MERGE_TIME: f32 = 0.25;
+
+pub fn update_damage_numbers(data: &mut GameData, ecs: &mut Ecs) {
+    let numbers = ecs.check_components(|e, comps| {
+        comps.damage_numbers.contains_key(e)
+            && comps.positions.contains_key(e)
+            && comps.timers.contains_key(e)
+    });
+
+    for number_e in &numbers {
+        let timer = ecs.components.timers.get(number_e).unwrap();
+        if timer.completed() {
+            ecs.despawn(*number_e);
+            continue;
+        }
+        let position = ecs.components.positions.get_mut(number_e).unwrap();
+        position.y -= 20. * get_frame_time();
+    }
+
+    for dealt in data.damage_dealt.clone() {
+        if !ecs.components.enemies.contains_key(&dealt.target) || dealt.amount <= 0. {
+            continue;
+        }
+        let fresh = numbers.iter().find(|number_e| {
+            let number = ecs.components.damage_numbers.get(number_e).unwrap();
+            let timer = ecs.components.timers.get(number_e).unwrap();
+            number.target == dealt.target
+                && !number.crit
+                && !dealt.crit
+                && timer.time * (1. - timer.progress()) < MERGE_TIME
+        });
+        match fresh {
+            Some(number_e) => {
+                let number = ecs.components.damage_numbers.get_mut(number_e).unwrap();
+                number.amount += dealt.amount;
+            }
+            None => {
+                spawn_damage_number(data, ecs, &dealt);
+            }
+        }
+    }
+}
+
+pub fn draw_damage_numbers(data: &GameData, ecs: &Ecs) {
+    let numbers = ecs.check_components(|e, comps| {
+        comps.damage_numbers.contains_key(e)
+            && comps.positions.contains_key(e)
+            && comps.timers.contains_key(e)
+    });
+
+    for number_e in &numbers {
+        let number = ecs.components.damage_numbers.get(number_e).unwrap();
+        let position = ecs.components.positions.get(number_e).unwrap();
+        let timer = ecs.components.timers.get(number_e).unwrap();
+
+        let text = if number.crit {
+            format!("{}!", (number.amount * 10.).round() / 10.)
+        } else {
+            format!("{}", (number.amount * 10.).round() / 10.)
+        };
+        let center = get_text_center(&text, Some(&data.ui.font), 16, 1., 0.);
+        let x = (position.x - center.x).floor();
+        let y = position.y.floor();
+        let mut color = if number.crit { YELLOW } else { number.color };
+        color.a = (timer.progress() * 3.).min(1.);
+        let mut shadow = data.ui.text_shadow_color;
+        shadow.a = color.a;
+
+        for (offset, color) in [(1., shadow), (0., color)] {
+            draw_text_ex(
+                &text,
+                x,
+                y + offset,
+                TextParams {
+                    font: Some(&data.ui.font),
+                    font_size: 16,
+                    color,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
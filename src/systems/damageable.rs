@@ -2,18 +2,20 @@ use std::collections::HashMap;
 
 use crate::{
     entity::{
+        damage::{Damage, DamageDealt},
+        elite::EliteAffix,
         entities::Ecs,
         entity_id::Entity,
         events::{DamageEvent, DeathEvent},
+        hopper::spawn_hopper,
         impact::{spawn_dust, splatter_blood},
-        mirituhg::{self, spawn_mirituhg_death, MiritughState},
+        mirituhg::{self, spawn_mirituhg_death, Mirituhg},
         pickup::{spawn_pickup, Pickup},
         projectile::spawn_bullet,
         skull::spawn_skull,
         tags::EntityType,
     },
     game_data::GameData,
-    items::weapon::Weapon,
     physics::collision::Collision,
 };
 use macroquad::{
@@ -21,7 +23,11 @@ use macroquad::{
     prelude::*,
 };
 
-use super::collision::ColliderType;
+use super::{
+    collision::ColliderType,
+    projectile::{spawn_splits, survives_hit, take_split},
+    weapon::spawn_shots,
+};
 
 pub fn update_damageables(ecs: &mut Ecs) {
     let damageables = ecs.check_components(|e, comps| comps.damageables.contains_key(e));
@@ -47,6 +53,20 @@ pub fn flash_on_damage(ecs: &mut Ecs) {
     for damageable_e in &damageables {
         let damageable = ecs.components.damageables.get_mut(damageable_e).unwrap();
         let material = ecs.components.materials.get_mut(damageable_e).unwrap();
+        let tint = ecs
+            .components
+            .status_effects
+            .get(damageable_e)
+            .and_then(|status| status.tint())
+            .or_else(|| ecs.components.elites.get(damageable_e).map(|e| e.tint()))
+            .or_else(|| {
+                ecs.components
+                    .mirituhg
+                    .get(damageable_e)
+                    .and(ecs.components.animated_sprites.get(damageable_e))
+                    .and_then(|sprite| Mirituhg::telegraph_tint(&sprite.current_animation))
+            })
+            .unwrap_or(WHITE);
 
         if let Some(hit_fx_timer) = &mut damageable.hit_fx_timer {
             if !hit_fx_timer.completed() {
@@ -58,13 +78,64 @@ pub fn flash_on_damage(ecs: &mut Ecs) {
                 color.a = (0.5 - hit_fx_timer.progress() % 0.5) * 2.;
                 material.set_uniform("color", color);
             } else {
-                material.set_uniform("color", WHITE);
+                material.set_uniform("color", tint);
             }
         }
     }
 }
 
+/// Damage over time that bypasses invulnerability and only flashes when the last flash is done.
+fn apply_continuous_damage(data: &mut GameData, ecs: &mut Ecs, event: &DamageEvent) {
+    let (Some(damageable), Some(health)) = (
+        ecs.components.damageables.get_mut(&event.target),
+        ecs.components.health.get_mut(&event.target),
+    ) else {
+        return;
+    };
+
+    let (amount, crit) = event
+        .damage
+        .roll(ecs.components.resistances.get(&event.target));
+    let amount = match ecs.components.elites.get_mut(&event.target) {
+        Some(elite) => elite.absorb(amount),
+        None => amount,
+    };
+    health.hp -= amount;
+
+    if let Some(hit_fx_timer) = &mut damageable.hit_fx_timer {
+        if hit_fx_timer.completed() {
+            hit_fx_timer.reset();
+        }
+    }
+    if !event.statuses.is_empty() {
+        let status_effects = ecs
+            .components
+            .status_effects
+            .entry(event.target)
+            .or_default();
+        for status in &event.statuses {
+            status_effects.apply(*status);
+        }
+    }
+    if let Some(position) = ecs.components.positions.get(&event.target) {
+        data.damage_dealt.push(DamageDealt {
+            target: event.target,
+            position: *position,
+            amount,
+            kind: event.damage.kind,
+            crit,
+        });
+    }
+}
+
 pub fn apply_damage(data: &mut GameData, ecs: &mut Ecs, damage_events: &mut Vec<DamageEvent>) {
+    damage_events.retain(|event| {
+        if event.continuous {
+            apply_continuous_damage(data, ecs, event);
+        }
+        !event.continuous
+    });
+
     let damageables = ecs.check_components(|e, comps| {
         comps.damageables.contains_key(e) && comps.health.contains_key(e)
     });
@@ -90,7 +161,7 @@ pub fn apply_damage(data: &mut GameData, ecs: &mut Ecs, damage_events: &mut Vec<
         // TODO: use indices instead
         let event = damage_events.iter().find(|e| e.target == *damageable_e);
 
-        let mut bullets = vec![];
+        let mut shots = vec![];
 
         if let Some(event) = event {
             if let Some(invulnerable_timer) = &mut damageable.invulnerable_timer {
@@ -100,43 +171,64 @@ pub fn apply_damage(data: &mut GameData, ecs: &mut Ecs, damage_events: &mut Vec<
 
                     let mut apply_damage = true;
                     if is_player {
-                        if let Weapon::Dash(ref dash) = data.weapon {
-                            if dash.dashing {
-                                apply_damage = false;
-                            } else {
-                                audio::play_sound(
-                                    &data.audio.hit2,
-                                    PlaySoundParams {
-                                        volume: data.settings.sfx_volume,
-                                        ..Default::default()
-                                    },
-                                );
-                            }
+                        if data.weapon.evading() {
+                            apply_damage = false;
+                        } else {
+                            audio::play_sound(
+                                &data.audio.hit2,
+                                PlaySoundParams {
+                                    volume: data.settings.sfx_volume,
+                                    ..Default::default()
+                                },
+                            );
                         }
                     }
-                    if is_enemy {
-                        let coll = ecs.components.colliders.get(&event.source).unwrap();
-
-                        if coll.coll_type == ColliderType::ProjectileWithoutMapCollision {
-                            if let Weapon::Balls(ref balls) = data.weapon {
-                                let up_data = balls.get_upgraded_data();
-                                if up_data.bullets {
-                                    let pos = ecs.components.positions.get(&event.target).unwrap();
-                                    bullets.push(*pos);
-                                }
-                            }
-                        }
-                        if let Weapon::Dash(ref balls) = data.weapon {
-                            let up_data = balls.get_upgraded_data();
-                            if up_data.bullets {
-                                let pos = ecs.components.positions.get(&event.target).unwrap();
-                                bullets.push(*pos);
-                            }
-                        }
+                    // Hazard tiles deal damage without a collider
+                    let coll = ecs.components.colliders.get(&event.source);
+                    if let (true, Some(coll)) = (is_enemy, coll) {
+                        let pos = ecs.components.positions.get(&event.target).unwrap();
+                        shots.extend(data.weapon.on_hit(*pos, &coll.coll_type));
                     }
 
                     if apply_damage {
-                        health.hp -= event.damage;
+                        let (amount, crit) = event
+                            .damage
+                            .roll(ecs.components.resistances.get(damageable_e));
+                        let amount = match ecs.components.elites.get_mut(damageable_e) {
+                            Some(elite) => elite.absorb(amount),
+                            None => amount,
+                        };
+                        health.hp -= amount;
+                        if let Some(position) = ecs.components.positions.get(damageable_e) {
+                            data.damage_dealt.push(DamageDealt {
+                                target: *damageable_e,
+                                position: *position,
+                                amount,
+                                kind: event.damage.kind,
+                                crit,
+                            });
+                        }
+                        if let Some(impulse) = event.impulse {
+                            let resistance = ecs
+                                .components
+                                .knockback_resistance
+                                .get(damageable_e)
+                                .copied()
+                                .unwrap_or(0.);
+                            ecs.components
+                                .knockbacks
+                                .insert(*damageable_e, impulse * (1. - resistance));
+                        }
+                        if !event.statuses.is_empty() {
+                            let status_effects = ecs
+                                .components
+                                .status_effects
+                                .entry(*damageable_e)
+                                .or_default();
+                            for status in &event.statuses {
+                                status_effects.apply(*status);
+                            }
+                        }
                         if is_player {
                             data.screen_shake.shake(0.25, 8.);
                         }
@@ -165,46 +257,7 @@ pub fn apply_damage(data: &mut GameData, ecs: &mut Ecs, damage_events: &mut Vec<
             }
         }
 
-        let bullet_vel = 50.;
-        let bullet_damage = 7.;
-        for pos in bullets {
-            spawn_bullet(
-                data,
-                ecs,
-                pos + vec2(1., 0.) * 12.,
-                EntityType::Enemy,
-                bullet_damage,
-                vec2(1., 0.) * bullet_vel,
-                ColliderType::PlayerProjectile,
-            );
-            spawn_bullet(
-                data,
-                ecs,
-                pos + vec2(0., 1.) * 12.,
-                EntityType::Enemy,
-                bullet_damage,
-                vec2(0., 1.) * bullet_vel,
-                ColliderType::PlayerProjectile,
-            );
-            spawn_bullet(
-                data,
-                ecs,
-                pos + vec2(-1., 0.) * 12.,
-                EntityType::Enemy,
-                bullet_damage,
-                vec2(-1., 0.) * bullet_vel,
-                ColliderType::PlayerProjectile,
-            );
-            spawn_bullet(
-                data,
-                ecs,
-                pos + vec2(0., -1.) * 12.,
-                EntityType::Enemy,
-                bullet_damage,
-                vec2(0., -1.) * bullet_vel,
-                ColliderType::PlayerProjectile,
-            );
-        }
+        spawn_shots(data, ecs, shots);
     }
 
     for pos in &splatter_positions {
@@ -220,6 +273,10 @@ pub fn damage_on_collision(
     collisions: &HashMap<(Entity, Entity), Collision>,
 ) {
     let damageables = ecs.check_components(|e, comps| comps.damageables.contains_key(e));
+    let player_crit = ecs.components.player_data.values().next().map(|player| {
+        let up_data = player.get_upgraded_data();
+        (up_data.crit_chance, up_data.crit_multiplier)
+    });
 
     for damageable_e in &damageables {
         for ((source, target), _collision) in collisions.iter() {
@@ -234,10 +291,42 @@ pub fn damage_on_collision(
                         damage_on_coll.source == EntityType::Player
                     };
                     if apply_damage {
+                        let impulse = match (
+                            ecs.components.positions.get(e1),
+                            ecs.components.positions.get(e2),
+                        ) {
+                            (Some(from), Some(to)) if damage_on_coll.knockback > 0. => {
+                                let mut dir = (*to - *from).normalize_or_zero();
+                                if dir == Vec2::ZERO {
+                                    dir = ecs
+                                        .components
+                                        .velocities
+                                        .get(e1)
+                                        .map_or(Vec2::ZERO, |vel| vel.normalize_or_zero());
+                                }
+                                Some(dir * damage_on_coll.knockback)
+                            }
+                            _ => None,
+                        };
+                        let mut damage =
+                            Damage::new(damage_on_coll.damage, damage_on_coll.damage_type);
+                        if let (EntityType::Player, Some((chance, multiplier))) =
+                            (&damage_on_coll.source, player_crit)
+                        {
+                            damage = damage.with_crit(chance, multiplier);
+                        }
                         damage_events.push(DamageEvent {
                             source: *e1,
                             target: *e2,
-                            damage: damage_on_coll.damage,
+                            damage,
+                            statuses: ecs
+                                .components
+                                .status_on_hit
+                                .get(e1)
+                                .cloned()
+                                .unwrap_or_default(),
+                            impulse,
+                            continuous: false,
                         });
                     }
                 }
@@ -252,11 +341,16 @@ pub fn despawn_on_collision(
     collisions: &HashMap<(Entity, Entity), Collision>,
 ) {
     let despawn_on_hits = ecs.check_components(|e, comps| comps.despawn_on_hit.contains_key(e));
+    let mut weapon_pickups = vec![];
+    let mut splits = vec![];
 
     for despawn_e in &despawn_on_hits {
-        for ((source, target), _collision) in collisions.iter() {
+        for ((source, target), collision) in collisions.iter() {
             for (e1, e2) in [(source, target), (target, source)] {
                 if e1 == despawn_e {
+                    if survives_hit(ecs, *despawn_e, *e2, collision) {
+                        continue;
+                    }
                     let despawn_on_hit = ecs.components.despawn_on_hit.get(despawn_e).unwrap();
                     // TODO: not safe
                     let position = ecs.components.positions.get(despawn_e).unwrap();
@@ -291,6 +385,15 @@ pub fn despawn_on_collision(
                                     player.aberration = (player.aberration - 0.02).max(0.);
                                     true
                                 }
+                                // Replacing a held weapon needs an explicit swap
+                                Pickup::Weapon(weapon_type) => {
+                                    let fills_slot = data.secondary_weapon.is_none()
+                                        && !data.holds_weapon(weapon_type);
+                                    if fills_slot {
+                                        weapon_pickups.push((*despawn_e, weapon_type.clone()));
+                                    }
+                                    fills_slot
+                                }
                             } {
                                 spawn_dust(data, ecs, *position);
                                 ecs.despawn(*despawn_e);
@@ -308,13 +411,25 @@ pub fn despawn_on_collision(
                         }
                     }
 
-                    spawn_dust(data, ecs, *position);
+                    let position = *position;
+                    splits.extend(take_split(ecs, *despawn_e));
+                    spawn_dust(data, ecs, position);
                     ecs.despawn(*despawn_e);
                     break;
                 }
             }
         }
     }
+
+    spawn_splits(data, ecs, splits);
+    for (pickup_e, weapon_type) in weapon_pickups {
+        let weapon = ecs
+            .components
+            .dropped_weapons
+            .remove(&pickup_e)
+            .unwrap_or_else(|| weapon_type.create());
+        data.pick_up_weapon(weapon);
+    }
 }
 
 pub fn kill_entities(data: &GameData, ecs: &mut Ecs, death_events: &mut Vec<DeathEvent>) {
@@ -346,11 +461,15 @@ pub fn handle_death(data: &mut GameData, ecs: &mut Ecs, death_events: &Vec<Death
     let mut skull_positions = vec![];
     let mut pickups = vec![];
 
+    let mut splits = vec![];
+    let mut explosions = vec![];
+
     let mut spawn_death = None;
     for ev in death_events {
         let pos = ecs.components.positions.get(&ev.0).unwrap();
         let player = ecs.components.player_entity.get(&ev.0);
-        let mirituhg = ecs.components.mirituhg.get_mut(&ev.0);
+        let mirituhg = ecs.components.mirituhg.get(&ev.0);
+        let elite = ecs.components.elites.get(&ev.0);
 
         if player.is_some() {
             data.dead = true;
@@ -366,11 +485,28 @@ pub fn handle_death(data: &mut GameData, ecs: &mut Ecs, death_events: &Vec<Death
             for _ in 0..40 {
                 skull_positions.push(vec2(rand::gen_range(0., 360.), rand::gen_range(0., 240.)))
             }
-        } else if let Some(mirituhg) = mirituhg {
-            mirituhg.state = MiritughState::Dead;
+        } else if mirituhg.is_some() {
+            data.current_room.locked = false;
             spawn_death = Some(*pos);
         } else {
-            let rand = rand::gen_range(0, (12 - data.item_drop_chance_increase).max(4));
+            data.current_room.kills_in_wave += 1;
+            data.weapon.on_kill(*pos);
+            if let Some(elite) = elite {
+                if elite.has(EliteAffix::Splitting) {
+                    splits.push(*pos);
+                }
+                if elite.has(EliteAffix::Explosive) {
+                    explosions.push(*pos);
+                }
+                pickups.push((Pickup::Health(1.), *pos + vec2(4., 0.)));
+            }
+            // Elites always roll a drop from the table on top of their extra health
+            let drop_range = if elite.is_some() {
+                5
+            } else {
+                (12 - data.item_drop_chance_increase).max(4)
+            };
+            let rand = rand::gen_range(0, drop_range);
             match rand {
                 0..=1 => pickups.push((Pickup::Health(1.), *pos)),
                 2..=3 => pickups.push((Pickup::AnomalySmall, *pos)),
@@ -394,6 +530,30 @@ pub fn handle_death(data: &mut GameData, ecs: &mut Ecs, death_events: &Vec<Death
         spawn_mirituhg_death(data, pos, ecs);
     }
 
+    for pos in splits {
+        for offset in [vec2(-6., 0.), vec2(6., 0.)] {
+            spawn_hopper(data, pos + offset, ecs);
+        }
+        spawn_dust(data, ecs, pos);
+    }
+
+    for pos in explosions {
+        let bullet_count = 10;
+        for i in 0..bullet_count {
+            let dir = Vec2::from_angle(i as f32 / bullet_count as f32 * std::f32::consts::TAU);
+            spawn_bullet(
+                data,
+                ecs,
+                pos + dir * 6.,
+                EntityType::Player,
+                1.,
+                dir * 70.,
+                ColliderType::Projectile,
+            );
+        }
+        data.screen_shake.shake(0.3, 3.);
+    }
+
     for (pickup, pos) in pickups {
         spawn_pickup(data, pos, ecs, pickup);
     }
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use macroquad::{
+    audio::{self, PlaySoundParams},
+    prelude::*,
+};
+
+use crate::{
+    entity::{
+        entities::Ecs,
+        entity_id::Entity,
+        impact::spawn_dust,
+        pickup::{spawn_pickup, Pickup},
+        tags::EntityType,
+    },
+    game_data::GameData,
+    map::map::TileDrop,
+    physics::collision::Collision,
+};
+
+pub fn damage_map_tiles(
+    data: &mut GameData,
+    ecs: &mut Ecs,
+    collisions: &HashMap<(Entity, Entity), Collision>,
+) {
+    let map_id = data.current_map().id;
+
+    let mut destroyed_tiles = vec![];
+    for ((source, target), collision) in collisions.iter() {
+        if *target != map_id {
+            continue;
+        }
+        let tile = match collision.tile {
+            Some(tile) => tile,
+            None => continue,
+        };
+        let damage = match ecs.components.damage_on_collision.get(source) {
+            Some(damage_on_coll) if damage_on_coll.source == EntityType::Player => {
+                damage_on_coll.damage
+            }
+            _ => continue,
+        };
+
+        if let Some(destroyed) = data.current_map_mut().damage_tile(tile, damage) {
+            destroyed_tiles.push((tile, destroyed.drop));
+        }
+    }
+
+    for ((x, y), drop) in destroyed_tiles {
+        let position = vec2(x as f32 * 8. + 4., y as f32 * 8. + 4.);
+        spawn_dust(data, ecs, position);
+        data.screen_shake.shake(0.1, 2.);
+        audio::play_sound(
+            &data.audio.hit,
+            PlaySoundParams {
+                volume: data.settings.sfx_volume,
+                ..Default::default()
+            },
+        );
+
+        if let Some(drop) = drop {
+            let pickup = match drop {
+                TileDrop::Health => Pickup::Health(1.),
+                TileDrop::AnomalySmall => Pickup::AnomalySmall,
+                TileDrop::AnomalyBig => Pickup::AnomalyBig,
+                TileDrop::Random => match rand::gen_range(0, 4) {
+                    0 => Pickup::Health(1.),
+                    1 => Pickup::AnomalyBig,
+                    _ => Pickup::AnomalySmall,
+                },
+            };
+            spawn_pickup(data, position, ecs, pickup);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use macroquad::prelude::*;
+
+use crate::entity::{
+    elite::{EliteAffix, ELITE_REGEN_PER_SECOND, ELITE_SPEED_MULTIPLIER},
+    entities::Ecs,
+};
+
+pub fn update_elites(ecs: &mut Ecs) {
+    let elites = ecs
+        .check_components(|e, comps| comps.elites.contains_key(e) && comps.health.contains_key(e));
+
+    for elite_e in &elites {
+        let elite = ecs.components.elites.get_mut(elite_e).unwrap();
+        let health = ecs.components.health.get_mut(elite_e).unwrap();
+
+        elite.shield_regen_timer.update();
+        if elite.shield_regen_timer.completed() {
+            elite.shield =
+                (elite.shield + elite.max_shield * 0.25 * get_frame_time()).min(elite.max_shield);
+        }
+
+        if elite.has(EliteAffix::Regenerating) && health.hp > 0. {
+            health.hp = (health.hp + elite.max_hp * ELITE_REGEN_PER_SECOND * get_frame_time())
+                .min(elite.max_hp);
+        }
+
+        if elite.has(EliteAffix::Fast) {
+            if let Some(velocity) = ecs.components.velocities.get_mut(elite_e) {
+                *velocity *= ELITE_SPEED_MULTIPLIER;
+            }
+        }
+    }
+}
+
+pub fn draw_elite_health_bars(ecs: &Ecs) {
+    let elites = ecs.check_components(|e, comps| {
+        comps.elites.contains_key(e)
+            && comps.health.contains_key(e)
+            && comps.positions.contains_key(e)
+    });
+
+    for elite_e in &elites {
+        let elite = ecs.components.elites.get(elite_e).unwrap();
+        let health = ecs.components.health.get(elite_e).unwrap();
+        let position = ecs.components.positions.get(elite_e).unwrap();
+        if ecs
+            .components
+            .animated_sprites
+            .get(elite_e)
+            .map_or(false, |sprite| !sprite.visible)
+        {
+            continue;
+        }
+
+        let width = 14.;
+        let x = (position.x - width / 2.).floor();
+        let y = (position.y - 16.).floor();
+        draw_rectangle(x - 1., y - 1., width + 2., 4., BLACK);
+        draw_rectangle(
+            x,
+            y,
+            width * (health.hp / elite.max_hp).clamp(0., 1.),
+            2.,
+            Color::from_rgba(172, 50, 50, 255),
+        );
+        if elite.max_shield > 0. {
+            draw_rectangle(
+                x,
+                y,
+                width * (elite.shield / elite.max_shield).clamp(0., 1.),
+                1.,
+                EliteAffix::Shielded.color(),
+            );
+        }
+
+        for (i, affix) in elite.affixes.iter().enumerate() {
+            draw_rectangle(x + i as f32 * 3., y - 3., 2., 2., affix.color());
+        }
+    }
+}
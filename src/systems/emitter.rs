@@ -0,0 +1,157 @@
+use std::f32::consts::TAU;
+
+use macroquad::{
+    audio::{self, PlaySoundParams},
+    prelude::*,
+};
+
+use crate::{
+    bullet_pattern::ShotKind,
+    entity::{
+        emitter::{BulletEmitter, SpeedCurve},
+        entities::Ecs,
+        projectile::{spawn_bullet, ProjectileModifiers},
+        tags::EntityType,
+    },
+    game_data::GameData,
+    timer::Timer,
+};
+
+use super::collision::ColliderType;
+
+pub fn update_emitters(data: &mut GameData, ecs: &mut Ecs) {
+    let emitters = ecs.check_components(|e, comps| {
+        comps.bullet_emitters.contains_key(e) && comps.positions.contains_key(e)
+    });
+
+    let players = ecs.check_components(|e, comps| {
+        comps.player_data.contains_key(e) && comps.positions.contains_key(e)
+    });
+    let player_pos = players
+        .first()
+        .map(|player_e| *ecs.components.positions.get(player_e).unwrap());
+
+    let mut bullets = vec![];
+    let mut sound_volume: f32 = 0.;
+    for emitter_e in &emitters {
+        let emitter = ecs.components.bullet_emitters.get_mut(emitter_e).unwrap();
+        let origin = *ecs.components.positions.get(emitter_e).unwrap() + emitter.offset;
+
+        emitter.wait -= get_frame_time();
+        while emitter.wait <= 0. && !emitter.finished() {
+            fire_step(emitter, origin, player_pos, &mut bullets);
+            sound_volume = sound_volume.max(emitter.pattern.steps[emitter.step].sound_volume);
+
+            emitter.repeat += 1;
+            let step = &emitter.pattern.steps[emitter.step];
+            if emitter.repeat < step.repeat_count() {
+                emitter.wait += step.interval;
+            } else {
+                emitter.step += 1;
+                emitter.repeat = 0;
+                if let Some(next_step) = emitter.pattern.steps.get(emitter.step) {
+                    emitter.wait += next_step.delay;
+                }
+            }
+        }
+
+        if emitter.finished() {
+            ecs.components.bullet_emitters.remove(emitter_e);
+        }
+    }
+
+    if sound_volume > 0. {
+        audio::play_sound(
+            &data.audio.shoot,
+            PlaySoundParams {
+                volume: data.settings.sfx_volume * sound_volume,
+                ..Default::default()
+            },
+        );
+    }
+
+    for (pos, vel, curve, modifiers) in bullets {
+        let bullet = spawn_bullet(
+            data,
+            ecs,
+            pos,
+            EntityType::Player,
+            1.,
+            vel,
+            ColliderType::Projectile,
+        );
+        if let Some(curve) = curve {
+            ecs.components.speed_curves.insert(bullet, curve);
+        }
+        modifiers.apply(ecs, bullet);
+    }
+}
+
+fn fire_step(
+    emitter: &BulletEmitter,
+    origin: Vec2,
+    player_pos: Option<Vec2>,
+    bullets: &mut Vec<(Vec2, Vec2, Option<SpeedCurve>, ProjectileModifiers)>,
+) {
+    let step = &emitter.pattern.steps[emitter.step];
+    let base_angle =
+        emitter.rotation + (step.angle + step.spin * emitter.repeat as f32).to_radians();
+
+    let angles = match step.shot_kind() {
+        ShotKind::Ring => (0..step.count)
+            .map(|i| base_angle + TAU / step.count as f32 * i as f32)
+            .collect::<Vec<_>>(),
+        ShotKind::Spread | ShotKind::Aimed => {
+            let center = match (step.shot_kind(), player_pos) {
+                (ShotKind::Aimed, Some(player_pos)) => {
+                    let to_player = player_pos - origin;
+                    to_player.y.atan2(to_player.x) + base_angle
+                }
+                _ => base_angle,
+            };
+            let arc = step.arc.to_radians();
+            let gap = if step.count > 1 {
+                arc / (step.count - 1) as f32
+            } else {
+                0.
+            };
+            (0..step.count)
+                .map(|i| center - arc / 2. + gap * i as f32)
+                .collect::<Vec<_>>()
+        }
+    };
+
+    for angle in angles {
+        let curve = step.end_speed.map(|end_speed| SpeedCurve {
+            start_speed: step.speed,
+            end_speed,
+            timer: Timer::new(step.speed_time.max(0.01), false),
+        });
+        bullets.push((
+            origin,
+            Vec2::from_angle(angle) * step.speed,
+            curve,
+            step.modifiers(),
+        ));
+    }
+}
+
+pub fn update_speed_curves(ecs: &mut Ecs) {
+    let curved = ecs.check_components(|e, comps| {
+        comps.speed_curves.contains_key(e) && comps.velocities.contains_key(e)
+    });
+
+    for curved_e in &curved {
+        let curve = ecs.components.speed_curves.get_mut(curved_e).unwrap();
+        let velocity = ecs.components.velocities.get_mut(curved_e).unwrap();
+
+        curve.timer.update();
+        let t = 1. - curve.timer.progress();
+        let speed = curve.start_speed + (curve.end_speed - curve.start_speed) * t;
+        *velocity = velocity.normalize_or_zero() * speed;
+
+        if curve.timer.completed() {
+            ecs.components.speed_curves.remove(curved_e);
+        }
+    }
+}
@@ -1,9 +1,20 @@
+pub mod ai;
 pub mod collision;
+pub mod damage_number;
 pub mod damageable;
+pub mod destructible;
+pub mod elite;
+pub mod emitter;
 pub mod enemy;
 pub mod movement;
+pub mod navigation;
+pub mod pickup;
 pub mod player;
+pub mod projectile;
 pub mod spawn;
 pub mod sprite;
+pub mod status;
+pub mod steering;
+pub mod tile_effect;
 pub mod timer;
 pub mod weapon;
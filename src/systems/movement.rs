@@ -8,6 +8,8 @@ use crate::{
     physics::collision::{resolve_circle_collision, resolve_map_collision, Collision},
 };
 
+const KNOCKBACK_DECAY: f32 = 12.;
+
 pub fn move_entities(data: &mut GameData, ecs: &mut Ecs) -> HashMap<(Entity, Entity), Collision> {
     let moveables = ecs.check_components(|e, comps| {
         comps.positions.contains_key(e) && comps.velocities.contains_key(e)
@@ -30,8 +32,13 @@ pub fn move_entities(data: &mut GameData, ecs: &mut Ecs) -> HashMap<(Entity, Ent
         let position = comps.positions.get_mut(moveable_e).unwrap();
         let velocity = comps.velocities.get(moveable_e).unwrap();
         let collider = comps.colliders.get(moveable_e);
+        let knockback = comps
+            .knockbacks
+            .get(moveable_e)
+            .copied()
+            .unwrap_or(Vec2::ZERO);
 
-        let mut desired_pos = *position + *velocity * get_frame_time();
+        let mut desired_pos = *position + (*velocity + knockback) * get_frame_time();
         if desired_pos.x.is_nan() || desired_pos.y.is_nan() {
             failed_entities.push(*moveable_e);
             println!("WTF {:?} {:?} {:?}", *position, velocity, get_frame_time());
@@ -45,10 +52,21 @@ pub fn move_entities(data: &mut GameData, ecs: &mut Ecs) -> HashMap<(Entity, Ent
             desired_pos = pos;
             let (pos, new_collisions) =
                 resolve_map_collision(*moveable_e, data, data.current_map(), desired_pos, collider);
+            // Walls stop knockback instead of pinning the entity against them
+            if !new_collisions.is_empty() {
+                comps.knockbacks.remove(moveable_e);
+            }
             collisions.extend(new_collisions);
             desired_pos = pos;
         }
         *position = desired_pos;
+
+        if let Some(knockback) = comps.knockbacks.get_mut(moveable_e) {
+            *knockback *= (-KNOCKBACK_DECAY * get_frame_time()).exp();
+            if knockback.length_squared() < 1. {
+                comps.knockbacks.remove(moveable_e);
+            }
+        }
     }
 
     // TODO: emergency fix to issue that prevent room completion
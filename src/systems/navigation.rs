@@ -0,0 +1,13 @@
+use crate::{entity::entities::Ecs, game_data::GameData};
+
+pub fn update_navigation(data: &mut GameData, ecs: &Ecs) {
+    let players = ecs.check_components(|e, comps| {
+        comps.player_data.contains_key(e) && comps.positions.contains_key(e)
+    });
+
+    for player_e in &players {
+        let player_pos = ecs.components.positions.get(player_e).unwrap();
+        data.navigation
+            .update(&data.maps[data.current_room.map_index], *player_pos);
+    }
+}
@@ -0,0 +1,101 @@
+use macroquad::prelude::*;
+
+use crate::{
+    entity::{
+        entities::Ecs,
+        entity_id::Entity,
+        pickup::{spawn_pickup, Pickup},
+    },
+    game_data::GameData,
+};
+
+/// Weapon pickup the player is standing on that isn't one of the held weapons.
+fn weapon_pickup_under(data: &GameData, ecs: &Ecs, player_e: Entity) -> Option<Entity> {
+    let player_pos = *ecs.components.positions.get(&player_e)?;
+    let player_radius = ecs.components.colliders.get(&player_e)?.radius;
+    ecs.check_components(|e, comps| {
+        comps.positions.contains_key(e) && comps.colliders.contains_key(e)
+    })
+    .into_iter()
+    .find(|pickup_e| {
+        let Some(Pickup::Weapon(weapon_type)) = ecs.components.pickups.get(pickup_e) else {
+            return false;
+        };
+        let position = ecs.components.positions.get(pickup_e).unwrap();
+        let radius = ecs.components.colliders.get(pickup_e).unwrap().radius;
+        !data.holds_weapon(weapon_type) && position.distance(player_pos) < player_radius + radius
+    })
+}
+
+/// Swaps the primary weapon for the weapon pickup under the player. The old weapon is dropped
+/// in its place and keeps its upgrades.
+pub fn swap_weapon_pickup(data: &mut GameData, ecs: &mut Ecs, player_e: Entity) -> bool {
+    let Some(pickup_e) = weapon_pickup_under(data, ecs, player_e) else {
+        return false;
+    };
+    let Some(Pickup::Weapon(weapon_type)) = ecs.components.pickups.get(&pickup_e) else {
+        return false;
+    };
+    let weapon = ecs
+        .components
+        .dropped_weapons
+        .remove(&pickup_e)
+        .unwrap_or_else(|| weapon_type.create());
+    let position = *ecs.components.positions.get(&pickup_e).unwrap();
+    ecs.despawn(pickup_e);
+
+    let dropped = data.replace_weapon(ecs, player_e, weapon);
+    let dropped_e = spawn_pickup(data, position, ecs, Pickup::Weapon(dropped.weapon_type()));
+    ecs.components.dropped_weapons.insert(dropped_e, dropped);
+    true
+}
+
+pub fn draw_weapon_pickups(data: &GameData, ecs: &Ecs) {
+    let pickups = ecs.check_components(|e, comps| {
+        comps.pickups.contains_key(e) && comps.positions.contains_key(e)
+    });
+    let swappable = ecs
+        .check_components(|e, comps| comps.player_data.contains_key(e))
+        .into_iter()
+        .filter_map(|player_e| weapon_pickup_under(data, ecs, player_e))
+        .collect::<Vec<_>>();
+
+    for pickup_e in &pickups {
+        let Pickup::Weapon(weapon_type) = ecs.components.pickups.get(pickup_e).unwrap() else {
+            continue;
+        };
+        let position = ecs.components.positions.get(pickup_e).unwrap();
+        let Some(texture) = data.graphics.textures.get(weapon_type.create().hud_icon()) else {
+            continue;
+        };
+
+        let size = vec2(texture.width(), texture.height()) / 2.;
+        let bob = ((get_time() * 3.).sin() as f32 * 1.5).round();
+        draw_texture_ex(
+            texture,
+            (position.x - size.x / 2.).floor(),
+            (position.y - size.y / 2.).floor() + bob,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(size),
+                ..Default::default()
+            },
+        );
+
+        if data.secondary_weapon.is_some() && swappable.contains(pickup_e) {
+            let text = "Swap";
+            let center = get_text_center(text, Some(&data.ui.font), 16, 1., 0.);
+            draw_text_ex(
+                text,
+                (position.x - center.x).floor(),
+                (position.y - size.y / 2.).floor() - 2.,
+                TextParams {
+                    font: Some(&data.ui.font),
+                    font_size: 16,
+                    color: data.ui.text_color,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
@@ -1,7 +1,10 @@
 use macroquad::prelude::*;
 
 use crate::{
-    entity::entities::Ecs, game_data::GameData, input_manager::Action, items::weapon::Weapon,
+    entity::entities::Ecs,
+    game_data::GameData,
+    input_manager::Action,
+    stats::{ModifierSource, Stat, StatModifier},
 };
 
 pub fn update_player(data: &mut GameData, ecs: &mut Ecs) {
@@ -15,6 +18,20 @@ pub fn update_player(data: &mut GameData, ecs: &mut Ecs) {
     for player in &players {
         let player_data = ecs.components.player_data.get_mut(player).unwrap();
         let velocity = ecs.components.velocities.get_mut(player).unwrap();
+
+        let slow = ecs
+            .components
+            .status_effects
+            .get(player)
+            .map_or(1., |status| status.speed_multiplier());
+        let status_modifiers = if slow < 1. {
+            vec![StatModifier::multiply(Stat::MoveSpeed, slow).from_source(ModifierSource::Status)]
+        } else {
+            vec![]
+        };
+        player_data
+            .stats
+            .set_source(ModifierSource::Status, status_modifiers);
         let up_data = player_data.get_upgraded_data();
 
         player_data.aberration_increase_timer.update();
@@ -29,11 +46,9 @@ pub fn update_player(data: &mut GameData, ecs: &mut Ecs) {
             .aberration_meter_material
             .set_uniform("intensity", player_data.aberration * 2.2);
 
-        if let Weapon::Dash(ref dash) = data.weapon {
-            if dash.dashing {
-                *velocity = dash.direction * dash.speed;
-                return;
-            }
+        if let Some(weapon_velocity) = data.weapon.movement_override() {
+            *velocity = weapon_velocity;
+            return;
         }
 
         let mut dir = Vec2::ZERO;
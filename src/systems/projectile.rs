@@ -0,0 +1,169 @@
+use std::f32::consts::TAU;
+
+use macroquad::prelude::*;
+
+use crate::{
+    entity::{
+        entities::Ecs,
+        entity_id::Entity,
+        impact::spawn_dust,
+        projectile::{spawn_bullet, SplitOnImpact},
+        tags::EntityType,
+    },
+    game_data::GameData,
+    physics::collision::Collision,
+};
+
+use super::collision::ColliderType;
+
+/// Steers homing bullets and fizzles out bullets that reached their range.
+pub fn update_projectiles(data: &mut GameData, ecs: &mut Ecs) {
+    let homing = ecs.check_components(|e, comps| {
+        comps.homing.contains_key(e)
+            && comps.positions.contains_key(e)
+            && comps.velocities.contains_key(e)
+            && comps.despawn_on_hit.contains_key(e)
+    });
+
+    for homing_e in &homing {
+        let homing = *ecs.components.homing.get(homing_e).unwrap();
+        let position = *ecs.components.positions.get(homing_e).unwrap();
+        let target = ecs.components.despawn_on_hit.get(homing_e).unwrap().0;
+        let targets = ecs.check_components(|e, comps| {
+            let is_target = match target {
+                EntityType::Player => comps.player_data.contains_key(e),
+                _ => {
+                    comps.enemies.contains_key(e)
+                        && comps.health.contains_key(e)
+                        && comps
+                            .colliders
+                            .get(e)
+                            .map_or(false, |coll| coll.coll_type == ColliderType::Enemy)
+                }
+            };
+            is_target && comps.positions.contains_key(e)
+        });
+
+        let nearest = targets
+            .iter()
+            .map(|e| *ecs.components.positions.get(e).unwrap())
+            .filter(|pos| pos.distance(position) <= homing.range)
+            .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position)));
+        let Some(nearest) = nearest else {
+            continue;
+        };
+
+        let velocity = ecs.components.velocities.get_mut(homing_e).unwrap();
+        let max_turn = homing.turn_speed * get_frame_time();
+        let angle = velocity
+            .angle_between(nearest - position)
+            .clamp(-max_turn, max_turn);
+        *velocity = Vec2::from_angle(angle).rotate(*velocity);
+    }
+
+    let ranged = ecs.check_components(|e, comps| {
+        comps.projectile_ranges.contains_key(e) && comps.velocities.contains_key(e)
+    });
+    let mut expired = vec![];
+    for ranged_e in &ranged {
+        let speed = ecs.components.velocities.get(ranged_e).unwrap().length();
+        let range = ecs.components.projectile_ranges.get_mut(ranged_e).unwrap();
+        *range -= speed * get_frame_time();
+        if *range <= 0. {
+            expired.push(*ranged_e);
+        }
+    }
+    for expired_e in expired {
+        if let Some(position) = ecs.components.positions.get(&expired_e).copied() {
+            spawn_dust(data, ecs, position);
+        }
+        ecs.despawn(expired_e);
+    }
+}
+
+/// Lets piercing and bouncing bullets survive a collision. Returns false if the bullet
+/// should break.
+pub fn survives_hit(
+    ecs: &mut Ecs,
+    bullet_e: Entity,
+    other_e: Entity,
+    collision: &Collision,
+) -> bool {
+    if collision.tile.is_some() {
+        let Some(bounces) = ecs.components.bounces.get_mut(&bullet_e) else {
+            return false;
+        };
+        *bounces -= 1;
+        if *bounces == 0 {
+            ecs.components.bounces.remove(&bullet_e);
+        }
+
+        let position = *ecs.components.positions.get(&bullet_e).unwrap();
+        let normal = (position - collision.point).normalize_or_zero();
+        if let Some(velocity) = ecs.components.velocities.get_mut(&bullet_e) {
+            if velocity.dot(normal) < 0. {
+                *velocity -= 2. * velocity.dot(normal) * normal;
+            }
+        }
+        if let Some(pierce) = ecs.components.pierces.get_mut(&bullet_e) {
+            pierce.hit.clear();
+        }
+        return true;
+    }
+
+    if !ecs.components.damageables.contains_key(&other_e) {
+        return false;
+    }
+    let Some(pierce) = ecs.components.pierces.get_mut(&bullet_e) else {
+        return false;
+    };
+    if pierce.hit.contains(&other_e) {
+        return true;
+    }
+    if pierce.remaining == 0 {
+        return false;
+    }
+    pierce.remaining -= 1;
+    pierce.hit.push(other_e);
+    true
+}
+
+/// A bullet that broke and still has to burst into its split bullets.
+pub struct PendingSplit {
+    position: Vec2,
+    split: SplitOnImpact,
+    damage: f32,
+    target: EntityType,
+    coll_type: ColliderType,
+}
+
+/// Takes the split of a breaking bullet before it despawns.
+pub fn take_split(ecs: &mut Ecs, bullet_e: Entity) -> Option<PendingSplit> {
+    let split = ecs.components.split_on_impact.remove(&bullet_e)?;
+    Some(PendingSplit {
+        position: *ecs.components.positions.get(&bullet_e)?,
+        split,
+        damage: ecs.components.damage_on_collision.get(&bullet_e)?.damage,
+        target: ecs.components.despawn_on_hit.get(&bullet_e)?.0,
+        coll_type: ecs.components.colliders.get(&bullet_e)?.coll_type,
+    })
+}
+
+pub fn spawn_splits(data: &mut GameData, ecs: &mut Ecs, splits: Vec<PendingSplit>) {
+    for pending in splits {
+        let offset = rand::gen_range(0., TAU);
+        let count = pending.split.count;
+        for i in 0..count {
+            let dir = Vec2::from_angle(offset + TAU / count as f32 * i as f32);
+            spawn_bullet(
+                data,
+                ecs,
+                pending.position + dir * 3.,
+                pending.target,
+                pending.damage * pending.split.damage_multiplier,
+                dir * pending.split.speed,
+                pending.coll_type,
+            );
+        }
+    }
+}
@@ -1,6 +1,18 @@
+use macroquad::{
+    audio::{self, PlaySoundParams},
+    prelude::*,
+};
+
 use crate::{
     entity::{
-        entities::Ecs, hopper::spawn_hopper, impact::spawn_dust, spitter::spawn_spitter,
+        burrower::spawn_burrower,
+        elite::{make_elite, EliteAffix},
+        entities::Ecs,
+        hopper::spawn_hopper,
+        impact::spawn_dust,
+        mirituhg::spawn_mirituhg,
+        spawn_marker::spawn_spawn_marker,
+        spitter::spawn_spitter,
         stomper::spawn_stomper,
     },
     game_data::GameData,
@@ -11,41 +23,164 @@ pub fn spawn_creatures(data: &mut GameData, ecs: &mut Ecs) {
     let spawners = ecs.check_components(|e, comps| {
         comps.positions.contains_key(e) && comps.spawners.contains_key(e)
     });
+    let markers = ecs.check_components(|e, comps| {
+        comps.positions.contains_key(e) && comps.spawn_markers.contains_key(e)
+    });
+    let enemies = ecs.check_components(|e, comps| comps.enemies.contains_key(e));
+
+    let players = ecs.check_components(|e, comps| {
+        comps.player_data.contains_key(e) && comps.positions.contains_key(e)
+    });
+    let player_pos = {
+        let mut pos = vec2(180., 120.);
+        for player_e in &players {
+            pos = *ecs.components.positions.get(player_e).unwrap();
+            break;
+        }
+        pos
+    };
+
+    let room = &mut data.current_room;
+    room.wave_timer.update();
+
+    let wave_due = room.waves_spawned == 0
+        || room.kills_in_wave >= room.wave_config.kills_to_advance
+        || room.wave_timer.completed()
+        || enemies.len() + markers.len() == 0;
 
     let mut spawns = vec![];
-    for spawner_e in &spawners {
-        let spawner = ecs.components.spawners.get_mut(&spawner_e).unwrap();
-        let position = ecs.components.positions.get(&spawner_e).unwrap();
-        if !spawner.active {
-            continue;
+    if wave_due && room.enemies_to_spawn.len() > 0 {
+        let mut candidates = spawners
+            .iter()
+            .filter(|e| ecs.components.spawners.get(e).unwrap().active)
+            .map(|e| *e)
+            .collect::<Vec<_>>();
+        // Don't materialise enemies right on top of the player if there's another option
+        let far_candidates = candidates
+            .iter()
+            .filter(|e| {
+                ecs.components
+                    .positions
+                    .get(e)
+                    .unwrap()
+                    .distance(player_pos)
+                    > 32.
+            })
+            .map(|e| *e)
+            .collect::<Vec<_>>();
+        if far_candidates.len() > 0 {
+            candidates = far_candidates;
         }
-        if position.x.is_nan() || position.y.is_nan() {}
-        spawns.push(position.clone());
-        if data.current_room.enemies_to_spawn.len() == 0 {
-            spawner.active = false;
+
+        if candidates.len() > 0 {
+            for _ in 0..room.wave_config.wave_size {
+                let enemy = match room.enemies_to_spawn.pop() {
+                    Some(enemy) => enemy,
+                    None => break,
+                };
+                candidates.sort_by(|a, b| {
+                    let a = ecs.components.spawners.get(a).unwrap().last_spawn_time;
+                    let b = ecs.components.spawners.get(b).unwrap().last_spawn_time;
+                    a.total_cmp(&b)
+                });
+                let spawner_e = candidates[0];
+                let spawner = ecs.components.spawners.get_mut(&spawner_e).unwrap();
+                spawner.last_spawn_time = get_time();
+                let position = *ecs.components.positions.get(&spawner_e).unwrap();
+                spawns.push((position, enemy));
+            }
+            room.kills_in_wave = 0;
+            room.wave_timer.reset();
+            room.waves_spawned += 1;
         }
     }
 
-    for spawn_pos in spawns {
-        if data.current_room.enemies_to_spawn.len() > 0 {
-            match data.current_room.enemies_to_spawn[data.current_room.enemies_to_spawn.len() - 1] {
-                Enemy::Hopper => {
-                    spawn_hopper(data, spawn_pos, ecs);
-                    spawn_dust(data, ecs, spawn_pos);
-                }
-                Enemy::Spitter => {
-                    spawn_spitter(data, spawn_pos, ecs);
-                    spawn_dust(data, ecs, spawn_pos);
-                }
-                Enemy::Stomper => {
-                    spawn_stomper(data, spawn_pos, ecs);
-                    spawn_dust(data, ecs, spawn_pos);
-                }
-                _ => todo!(),
-            }
-            data.current_room.enemies_to_spawn.pop();
+    if room.enemies_to_spawn.len() == 0 {
+        for spawner_e in &spawners {
+            ecs.components.spawners.get_mut(spawner_e).unwrap().active = false;
+        }
+    }
+
+    let warning_time = data.current_room.wave_config.spawn_warning_time;
+    let new_markers = spawns.len();
+    for (position, enemy) in spawns {
+        spawn_spawn_marker(data, ecs, position, enemy, warning_time);
+    }
+
+    let mut materialised = vec![];
+    for marker_e in &markers {
+        let marker = ecs.components.spawn_markers.get_mut(marker_e).unwrap();
+        marker.timer.update();
+        if marker.timer.completed() {
+            let position = *ecs.components.positions.get(marker_e).unwrap();
+            materialised.push((position, marker.enemy));
+            ecs.despawn(*marker_e);
         }
     }
 
-    data.current_room.entities_spawned = true;
+    for (spawn_pos, enemy) in &materialised {
+        let enemy_e = match enemy {
+            Enemy::Hopper => spawn_hopper(data, *spawn_pos, ecs),
+            Enemy::Spitter => spawn_spitter(data, *spawn_pos, ecs),
+            Enemy::Stomper => spawn_stomper(data, *spawn_pos, ecs),
+            Enemy::Burrower => spawn_burrower(data, *spawn_pos, ecs),
+            Enemy::Mirituhg => spawn_mirituhg(data, *spawn_pos, ecs),
+        };
+        let is_boss = matches!(enemy, Enemy::Mirituhg);
+        if !is_boss && rand::gen_range(0., 1.) < data.current_room.elite_chance {
+            make_elite(
+                ecs,
+                enemy_e,
+                EliteAffix::roll(data.current_room.elite_affixes),
+                data.difficulty.elites.hp_multiplier,
+            );
+        }
+        spawn_dust(data, ecs, *spawn_pos);
+    }
+    if materialised.len() > 0 {
+        audio::play_sound(
+            &data.audio.spawn,
+            PlaySoundParams {
+                volume: data.settings.sfx_volume * 0.6,
+                ..Default::default()
+            },
+        );
+    }
+
+    let pending_markers = markers.len() - materialised.len() + new_markers;
+    data.current_room.entities_spawned = spawners.len() == 0
+        || (data.current_room.enemies_to_spawn.len() == 0 && pending_markers == 0);
+}
+
+pub fn draw_spawn_markers(ecs: &Ecs) {
+    let markers = ecs.check_components(|e, comps| {
+        comps.positions.contains_key(e) && comps.spawn_markers.contains_key(e)
+    });
+
+    for marker_e in &markers {
+        let position = ecs.components.positions.get(marker_e).unwrap();
+        let marker = ecs.components.spawn_markers.get(marker_e).unwrap();
+
+        let progress = 1. - marker.timer.progress();
+        let blink = (get_time() * 12.).sin() as f32 * 0.5 + 0.5;
+        let color = Color::new(0.71, 0.13, 0.16, 0.35 + 0.5 * blink * progress);
+        let radius = 3. + 5. * progress;
+        draw_circle_lines(position.x, position.y, radius, 1., color);
+        draw_line(
+            position.x - 2.,
+            position.y - 2.,
+            position.x + 2.,
+            position.y + 2.,
+            1.,
+            color,
+        );
+        draw_line(
+            position.x + 2.,
+            position.y - 2.,
+            position.x - 2.,
+            position.y + 2.,
+            1.,
+            color,
+        );
+    }
 }
@@ -0,0 +1,32 @@
+use crate::entity::{entities::Ecs, events::DamageEvent};
+
+pub fn update_status_effects(ecs: &mut Ecs, damage_events: &mut Vec<DamageEvent>) {
+    let affected = ecs.check_components(|e, comps| comps.status_effects.contains_key(e));
+
+    for affected_e in &affected {
+        let status_effects = ecs.components.status_effects.get_mut(affected_e).unwrap();
+
+        // Ticks go through apply_damage so shields and hit flashes treat them like beams
+        damage_events.extend(
+            status_effects
+                .update()
+                .into_iter()
+                .map(|damage| DamageEvent {
+                    source: *affected_e,
+                    target: *affected_e,
+                    damage,
+                    statuses: vec![],
+                    impulse: None,
+                    continuous: true,
+                }),
+        );
+
+        // The player's slow goes through its move speed stat instead
+        if ecs.components.player_data.contains_key(affected_e) {
+            continue;
+        }
+        if let Some(velocity) = ecs.components.velocities.get_mut(affected_e) {
+            *velocity *= status_effects.speed_multiplier();
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use macroquad::prelude::*;
+
+use crate::entity::{ai::Steering, entity_id::Entity};
+
+pub struct SteeringInput<'a> {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub player_pos: Vec2,
+    /// Flow field direction towards the player.
+    pub seek_dir: Vec2,
+    pub neighbors: &'a Vec<(Entity, Vec2)>,
+    pub orbit_sign: f32,
+}
+
+/// Sums the weighted behaviours. The result is at most `speed` long.
+pub fn steering_velocity(
+    behaviors: &Vec<(Steering, f32)>,
+    speed: f32,
+    input: &SteeringInput,
+) -> Vec2 {
+    let mut steering = Vec2::ZERO;
+    for (behavior, weight) in behaviors {
+        steering += steering_force(behavior, input) * *weight;
+    }
+    steering.clamp_length_max(1.) * speed
+}
+
+fn steering_force(behavior: &Steering, input: &SteeringInput) -> Vec2 {
+    let to_player = input.player_pos - input.position;
+    let dist = to_player.length();
+    let dir = to_player.normalize_or_zero();
+
+    match behavior {
+        Steering::Seek => input.seek_dir,
+        Steering::Flee { within } => {
+            if dist < *within {
+                -dir * (1. - dist / within)
+            } else {
+                Vec2::ZERO
+            }
+        }
+        Steering::Arrive { distance, slowing } => {
+            input.seek_dir * ((dist - distance) / slowing).clamp(0., 1.)
+        }
+        Steering::Separation { radius } => {
+            let mut push = Vec2::ZERO;
+            for (other_e, other_pos) in input.neighbors {
+                if *other_e == input.entity {
+                    continue;
+                }
+                let away = input.position - *other_pos;
+                let other_dist = away.length();
+                if other_dist > 0. && other_dist < *radius {
+                    push += away / other_dist * (1. - other_dist / radius);
+                }
+            }
+            push.clamp_length_max(1.)
+        }
+        Steering::Orbit { radius } => {
+            let tangent = dir.perp() * input.orbit_sign;
+            let closeness = (2. - dist / radius).clamp(0., 1.);
+            let radial = dir * ((dist - radius) / radius).clamp(-1., 1.);
+            (tangent * closeness + radial).clamp_length_max(1.)
+        }
+    }
+}
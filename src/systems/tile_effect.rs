@@ -0,0 +1,83 @@
+use macroquad::prelude::*;
+
+use crate::{
+    entity::{damage::Damage, entities::Ecs, events::DamageEvent},
+    game_data::GameData,
+};
+
+pub fn apply_tile_effects(
+    data: &mut GameData,
+    ecs: &mut Ecs,
+    damage_events: &mut Vec<DamageEvent>,
+) {
+    let grounded = ecs.check_components(|e, comps| {
+        comps.positions.contains_key(e)
+            && comps.velocities.contains_key(e)
+            && comps.colliders.contains_key(e)
+            && (comps.player_data.contains_key(e) || comps.enemies.contains_key(e))
+            // Tunnelling burrowers are below the tiles
+            && comps.burrowers.get(e).map_or(true, |b| !b.submerged)
+    });
+
+    let map = data.current_map();
+    let map_id = map.id;
+
+    let dashing = data.weapon.evading();
+
+    for grounded_e in &grounded {
+        let position = ecs.components.positions.get_mut(grounded_e).unwrap();
+        let velocity = ecs.components.velocities.get_mut(grounded_e).unwrap();
+        let player_data = ecs.components.player_data.get_mut(grounded_e);
+
+        let effect = match map.tile_effect(*position) {
+            Some(effect) => effect,
+            None => {
+                if let Some(player_data) = player_data {
+                    player_data.last_safe_position = *position;
+                }
+                continue;
+            }
+        };
+
+        if let Some(slow) = effect.slow {
+            *velocity *= slow;
+        }
+
+        if let Some(damage) = effect.damage {
+            damage_events.push(DamageEvent {
+                source: map_id,
+                target: *grounded_e,
+                damage: Damage::physical(damage),
+                statuses: vec![],
+                impulse: None,
+                continuous: false,
+            });
+        }
+
+        if let Some(player_data) = player_data {
+            if let Some(aberration) = effect.aberration {
+                player_data.aberration =
+                    (player_data.aberration + aberration * get_frame_time()).clamp(0., 1.);
+            }
+
+            if effect.pit && !dashing {
+                *position = player_data.last_safe_position;
+                *velocity = Vec2::ZERO;
+                damage_events.push(DamageEvent {
+                    source: map_id,
+                    target: *grounded_e,
+                    damage: Damage::physical(1.),
+                    statuses: vec![],
+                    impulse: None,
+                    continuous: false,
+                });
+            } else if !effect.pit {
+                player_data.last_safe_position = *position;
+            }
+        } else if effect.pit {
+            if let Some(health) = ecs.components.health.get_mut(grounded_e) {
+                health.hp = 0.;
+            }
+        }
+    }
+}
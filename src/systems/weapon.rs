@@ -1,197 +1,116 @@
-use std::f32::consts::TAU;
-
 use macroquad::{
     audio::{self, PlaySoundParams},
-    input::{is_mouse_button_pressed, MouseButton},
-    math::{vec2, Vec2},
+    prelude::*,
 };
 
 use crate::{
-    entity::{
-        entities::Ecs,
-        projectile::spawn_bullet,
-        tags::{DamageOnCollision, EntityType},
-    },
+    entity::{entities::Ecs, events::DamageEvent, projectile::spawn_bullet, tags::EntityType},
     game_data::GameData,
     input_manager::Action,
-    items::weapon::Weapon,
+    items::weapon::{Shot, WeaponContext},
 };
 
-use super::collision::ColliderType;
-
-pub fn update_weapon(ecs: &mut Ecs, data: &mut GameData) {
-    let players = ecs.check_components(|e, comps| {
-        comps.player_data.contains_key(e) && comps.positions.contains_key(e)
-    });
+use super::{collision::ColliderType, pickup::swap_weapon_pickup};
+
+/// Half-angle of the auto-aim cone in radians, before the aim assist setting scales it.
+const AUTO_AIM_ANGLE: f32 = 0.2;
+const AUTO_AIM_RANGE: f32 = 160.;
+
+/// Snaps `aim_dir` to the closest visible enemy within `max_angle` radians, if any.
+pub fn auto_aim_dir(
+    data: &GameData,
+    ecs: &Ecs,
+    from: Vec2,
+    aim_dir: Vec2,
+    max_angle: f32,
+    range: f32,
+) -> Vec2 {
+    if max_angle <= 0. {
+        return aim_dir;
+    }
 
-    let ball_entities = ecs.check_components(|e, comps| {
-        comps.balls.contains_key(e)
+    let enemies = ecs.check_components(|e, comps| {
+        comps.enemies.contains_key(e)
             && comps.positions.contains_key(e)
-            && comps.velocities.contains_key(e)
+            && comps.health.contains_key(e)
+            && comps.burrowers.get(e).map_or(true, |b| !b.submerged)
     });
 
-    let mut bullet_data = Vec::<(f32, Vec2, Vec2, Option<usize>)>::new();
-
-    let mut player_pos = Vec2::ZERO;
-    for player_e in &players {
-        let player_position = ecs.components.positions.get_mut(&player_e).unwrap();
-        let player_data = ecs.components.player_data.get_mut(&player_e).unwrap();
-        player_pos = *player_position;
-        match &mut data.weapon {
-            Weapon::Launcher(ref mut launcher) => {
-                launcher.shoot_timer.update();
-
-                let launcher_data = launcher.get_upgraded_data();
-
-                if launcher.shoot_timer.completed() {
-                    launcher.shoot_timer.reset();
-
-                    let dir = data.input.get_aim_dir(&data.camera, player_pos);
-
-                    if launcher_data.double_bullets {
-                        let asdf = (dir.y / dir.x).atan();
-                        bullet_data.push((
-                            launcher_data.damage,
-                            *player_position + Vec2::from_angle(asdf + TAU / 6.) * 3.,
-                            dir * 160.,
-                            None,
-                        ));
-                        bullet_data.push((
-                            launcher_data.damage,
-                            *player_position + Vec2::from_angle(asdf - TAU / 6.) * 3.,
-                            dir * 160.,
-                            None,
-                        ));
-                    } else {
-                        bullet_data.push((
-                            launcher_data.damage,
-                            *player_position + dir * 3.,
-                            dir * 160.,
-                            None,
-                        ));
-                    }
-                }
-
-                launcher.shoot_timer.time = launcher_data.timer_duration;
-            }
-            Weapon::Balls(ref mut balls) => {
-                balls.update();
-
-                let balls_data = balls.get_upgraded_data();
-
-                if ball_entities.len() < balls_data.amount && balls.buffered_spawns > 0 {
-                    //
-                    // let missing_ball_index = ball_entities.iter().find(|e| e.0);
-                    let mut ball_ids = (0..balls_data.amount).collect::<Vec<usize>>();
-                    for ball_e in &ball_entities {
-                        let id = ecs.components.balls.get(ball_e).unwrap();
-                        if ball_ids.contains(id) {
-                            ball_ids = ball_ids
-                                .iter()
-                                .filter(|b_id| *b_id != id)
-                                .map(|b_id| *b_id)
-                                .collect();
-                        }
-                    }
-                    balls.buffered_spawns -= 1;
-                    bullet_data.push((
-                        balls.base_damage,
-                        *player_position,
-                        vec2(0., 0.),
-                        Some(ball_ids[0]),
-                    ));
-                    balls.ball_spawn_timer.reset();
-                }
-            }
-            Weapon::Dash(ref mut dash) => {
-                dash.update();
-                let dash_data = dash.get_upgraded_data();
-
-                if dash.dashing_timer.just_completed() {
-                    dash.dashing = false;
-                    for shadow_e in &player_data.shadows {
-                        let sprite = ecs.components.animated_sprites.get_mut(&shadow_e).unwrap();
-                        sprite.visible = false;
-                    }
-                    ecs.components.damage_on_collision.remove(player_e);
-                }
-                if dash.shadow_timer.just_completed() && dash.shadow_index < 2 {
-                    let shadow_e = player_data.shadows[dash.shadow_index];
-                    let sprite = ecs.components.animated_sprites.get_mut(&shadow_e).unwrap();
-                    let shadow_pos = ecs.components.positions.get_mut(&shadow_e).unwrap();
-                    sprite.visible = true;
-                    *shadow_pos = player_pos;
-                    dash.shadow_timer.reset();
-                    dash.shadow_index += 1;
-                }
-                if dash.dash_timer.completed() {
-                    if data.input.is_just_pressed(Action::Confirm) {
-                        dash.dash_timer.reset();
-                        dash.dashing_timer.reset();
-                        dash.shadow_timer.reset();
-                        ecs.components.damage_on_collision.insert(
-                            *player_e,
-                            DamageOnCollision {
-                                source: EntityType::Player,
-                                damage: dash_data.damage,
-                            },
-                        );
-                        dash.dashing = true;
-                        dash.shadow_index = 0;
-
-                        let mut dir = Vec2::ZERO;
-                        if data.input.is_currently_pressed(Action::Left) {
-                            dir += vec2(-1., 0.);
-                        }
-                        if data.input.is_currently_pressed(Action::Up) {
-                            dir += vec2(0., -1.);
-                        }
-                        if data.input.is_currently_pressed(Action::Right) {
-                            dir += vec2(1., 0.);
-                        }
-                        if data.input.is_currently_pressed(Action::Down) {
-                            dir += vec2(0., 1.);
-                        }
-                        if let Some(gamepad) = data.input.gamepads.get_last_used() {
-                            let input = vec2(gamepad.left_stick_x(), -gamepad.left_stick_y());
-                            if input.length_squared() > 0. {
-                                dir = input;
-                            }
-                        }
-
-                        dash.direction = if dir.length_squared() > 0. {
-                            dir.normalize()
-                        } else {
-                            Vec2::X
-                        };
-                    }
-                }
-                dash.dash_timer.time = dash_data.dash_timer_duration;
-            }
+    let map = data.current_map();
+    let mut best = None;
+    let mut best_angle = max_angle;
+    for enemy_e in &enemies {
+        let position = ecs.components.positions.get(enemy_e).unwrap();
+        let diff = *position - from;
+        if diff.length_squared() > range * range || diff.length_squared() <= 0. {
+            continue;
+        }
+        let angle = aim_dir.angle_between(diff).abs();
+        if angle < best_angle && map.has_line_of_sight(from, *position) {
+            best_angle = angle;
+            best = Some(diff.normalize());
         }
     }
 
-    for ball_e in &ball_entities {
-        if let Weapon::Balls(ref mut balls) = data.weapon {
-            let balls_data = balls.get_upgraded_data();
-            let position = ecs.components.positions.get_mut(ball_e).unwrap();
-            let ball_index = ecs.components.balls.get_mut(ball_e).unwrap();
+    best.unwrap_or(aim_dir)
+}
 
-            let angle = Vec2::from_angle(
-                TAU / balls_data.amount as f32 * *ball_index as f32 + TAU * balls.rotation_progress,
-            )
-            .rotate(Vec2::X);
+pub fn update_weapon(ecs: &mut Ecs, data: &mut GameData, damage_events: &mut Vec<DamageEvent>) {
+    let players = ecs.check_components(|e, comps| {
+        comps.player_data.contains_key(e) && comps.positions.contains_key(e)
+    });
+
+    let mut shots = vec![];
+    for player_e in &players {
+        let position = *ecs.components.positions.get(player_e).unwrap();
+        let dir = data.input.get_aim_dir(&data.camera, position);
+        let aim_dir = auto_aim_dir(
+            data,
+            ecs,
+            position,
+            dir,
+            AUTO_AIM_ANGLE * data.settings.aim_assist.strength(),
+            AUTO_AIM_RANGE,
+        );
 
-            let ball_distance = 24.;
-            let desired_pos = player_pos + angle * ball_distance;
+        if data.input.is_just_pressed(Action::Swap)
+            && (swap_weapon_pickup(data, ecs, *player_e) || data.swap_weapons(ecs, *player_e))
+        {
+            audio::play_sound(
+                &data.audio.ui_switch,
+                PlaySoundParams {
+                    volume: data.settings.sfx_volume * 0.6,
+                    ..Default::default()
+                },
+            );
+        }
 
-            *position = desired_pos;
-        } else {
-            ecs.despawn(*ball_e);
+        let ctx = WeaponContext {
+            input: &data.input,
+            player_e: *player_e,
+            player_pos: position,
+            aim_dir,
+            fire_mode: data.settings.fire_mode,
+            map: &data.maps[data.current_room.map_index],
         };
+        data.weapon.update(&ctx, ecs);
+        shots.extend(data.weapon.on_fire(&ctx, ecs));
+        let strikes = data.weapon.strike(&ctx, ecs);
+        let up_data = ecs
+            .components
+            .player_data
+            .get(player_e)
+            .unwrap()
+            .get_upgraded_data();
+        damage_events.extend(strikes.into_iter().map(|mut event| {
+            event.damage = event
+                .damage
+                .with_crit(up_data.crit_chance, up_data.crit_multiplier);
+            event
+        }));
     }
 
-    if bullet_data.len() > 0 {
+    if shots.len() > 0 {
         audio::play_sound(
             &data.audio.shoot,
             PlaySoundParams {
@@ -201,8 +120,14 @@ pub fn update_weapon(ecs: &mut Ecs, data: &mut GameData) {
         );
     }
 
-    for (damage, position, vel, bullet_index) in &bullet_data {
-        let coll_type = if bullet_index.is_some() {
+    spawn_shots(data, ecs, shots);
+}
+
+pub fn spawn_shots(data: &mut GameData, ecs: &mut Ecs, shots: Vec<Shot>) {
+    let statuses = data.weapon.stats().statuses;
+
+    for shot in shots {
+        let coll_type = if shot.ball_index.is_some() {
             ColliderType::ProjectileWithoutMapCollision
         } else {
             ColliderType::PlayerProjectile
@@ -210,14 +135,53 @@ pub fn update_weapon(ecs: &mut Ecs, data: &mut GameData) {
         let bullet_id = spawn_bullet(
             data,
             ecs,
-            *position,
+            shot.position,
             EntityType::Enemy,
-            *damage,
-            *vel,
+            shot.damage,
+            shot.velocity,
             coll_type,
         );
-        if let Some(bullet_index) = bullet_index {
-            ecs.components.balls.insert(bullet_id, *bullet_index);
+        shot.modifiers.apply(ecs, bullet_id);
+        if shot.scale != 1. {
+            if let Some(collider) = ecs.components.colliders.get_mut(&bullet_id) {
+                collider.radius *= shot.scale;
+            }
+            if let Some(sprite) = ecs.components.animated_sprites.get_mut(&bullet_id) {
+                sprite.scale = shot.scale;
+            }
+        }
+        if let Some(ball_index) = shot.ball_index {
+            ecs.components.balls.insert(bullet_id, ball_index);
         }
+        if !statuses.is_empty() {
+            ecs.components
+                .status_on_hit
+                .insert(bullet_id, statuses.clone());
+        }
+    }
+}
+
+/// Draws weapon effects plus the charge or heat meter below the player.
+pub fn draw_weapon(data: &GameData, ecs: &Ecs) {
+    data.weapon.draw();
+
+    let meter = match (data.weapon.charge(), data.weapon.heat()) {
+        (Some(charge), _) if charge >= 1. => (charge, Color::from_rgba(255, 230, 120, 255)),
+        (Some(charge), _) => (charge, Color::from_rgba(200, 200, 220, 255)),
+        (None, Some((heat, true))) => (heat, Color::from_rgba(172, 50, 50, 255)),
+        (None, Some((heat, false))) => (heat, Color::from_rgba(255, 160, 60, 255)),
+        (None, None) => return,
+    };
+    let players = ecs.check_components(|e, comps| {
+        comps.player_data.contains_key(e) && comps.positions.contains_key(e)
+    });
+
+    for player_e in &players {
+        let position = ecs.components.positions.get(player_e).unwrap();
+        let width = 12.;
+        let x = (position.x - width / 2.).floor();
+        let y = (position.y + 8.).floor();
+        draw_rectangle(x - 1., y - 1., width + 2., 3., BLACK);
+        draw_rectangle(x, y, (width * meter.0).round(), 1., meter.1);
     }
 }
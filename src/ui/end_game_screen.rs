@@ -6,7 +6,12 @@ use macroquad::{
     texture::{draw_texture, Texture2D},
 };
 
-use crate::{game_data::GameData, timer::Timer};
+use crate::{
+    game_data::{Audio, GameData},
+    input_manager::{Action, InputManager},
+    settings::GameSettings,
+    timer::Timer,
+};
 
 use super::button::button;
 
@@ -16,6 +21,12 @@ pub struct EndGameScreen {
     timer: Timer,
     text_timer: Timer,
     show_button_timer: Timer,
+    focus: usize,
+}
+
+pub enum EndGameChoice {
+    ContinueRun,
+    Finish,
 }
 
 impl EndGameScreen {
@@ -26,10 +37,11 @@ impl EndGameScreen {
             timer: Timer::new(1.5, false),
             text_timer: Timer::new(0.3, false),
             show_button_timer: Timer::new(5., false),
+            focus: 0,
         }
     }
 
-    pub fn update(&mut self) {
+    pub fn update(&mut self, input: &InputManager, audio: &Audio, settings: &GameSettings) {
         self.start_timer.update();
         self.timer.update();
         self.text_timer.update();
@@ -40,15 +52,29 @@ impl EndGameScreen {
             self.text_timer.reset();
             self.show_button_timer.reset();
         }
+
+        if self.show_button_timer.completed()
+            && (input.is_just_pressed(Action::Up) || input.is_just_pressed(Action::Down))
+        {
+            self.focus = 1 - self.focus;
+            audio::play_sound(
+                &audio.ui_switch,
+                PlaySoundParams {
+                    volume: settings.sfx_volume,
+                    ..Default::default()
+                },
+            );
+        }
     }
 
     pub fn show(&mut self) {
         self.start_timer.reset();
+        self.focus = 0;
     }
 
-    pub fn draw(&self, data: &GameData) -> bool {
+    pub fn draw(&self, data: &GameData) -> Option<EndGameChoice> {
         if !self.start_timer.completed() {
-            return false;
+            return None;
         }
         draw_rectangle_ex(
             0.,
@@ -72,30 +98,41 @@ impl EndGameScreen {
             ),
         );
 
-        let mut should_restart = false;
+        let mut choice = None;
 
-        // if self.show_button_timer.completed() {
-        //     let center = vec2(360. / 2., 240. / 2.);
-        //     let button_width = 90.;
-        //     if button(
-        //         &data,
-        //         &Rect::new(center.x - button_width / 2., 200., button_width, 20.),
-        //         true,
-        //         "Play Again",
-        //         None,
-        //         Vec2::ZERO,
-        //     ) {
-        //         should_restart = true;
-        //         audio::play_sound(
-        //             &data.audio.confirm,
-        //             PlaySoundParams {
-        //                 volume: data.settings.sfx_volume,
-        //                 ..Default::default()
-        //             },
-        //         );
-        //     }
-        // }
+        if self.show_button_timer.completed() {
+            let center = vec2(360. / 2., 240. / 2.);
+            let button_width = 110.;
+            let buttons = [
+                ("Descend Deeper", EndGameChoice::ContinueRun),
+                ("Finish Run", EndGameChoice::Finish),
+            ];
+            for (i, (text, button_choice)) in buttons.into_iter().enumerate() {
+                if button(
+                    &data,
+                    &Rect::new(
+                        center.x - button_width / 2.,
+                        180. + i as f32 * 24.,
+                        button_width,
+                        20.,
+                    ),
+                    self.focus == i,
+                    text,
+                    None,
+                    Vec2::ZERO,
+                ) {
+                    choice = Some(button_choice);
+                    audio::play_sound(
+                        &data.audio.confirm,
+                        PlaySoundParams {
+                            volume: data.settings.sfx_volume,
+                            ..Default::default()
+                        },
+                    );
+                }
+            }
+        }
 
-        should_restart
+        choice
     }
 }
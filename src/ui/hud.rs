@@ -1,9 +1,11 @@
 use macroquad::{
+    color::Color,
     material::{gl_use_default_material, gl_use_material, load_material, Material, MaterialParams},
     math::{vec2, Vec2},
     miniquad::{
         BlendFactor, BlendState, BlendValue, Equation, PipelineParams, ShaderSource, UniformType,
     },
+    texture::{draw_texture_ex, DrawTextureParams},
 };
 
 use crate::{entity::entities::Ecs, game_data::GameData, sprite::indexed_sprite::IndexedSprite};
@@ -43,6 +45,43 @@ impl HudHearts {
     }
 }
 
+pub struct HudWeapon;
+
+impl HudWeapon {
+    pub fn draw(&self, data: &GameData) {
+        let Some(texture) = data.graphics.textures.get(data.weapon.hud_icon()) else {
+            return;
+        };
+        draw_texture_ex(
+            texture,
+            16.,
+            17.,
+            Color::new(1., 1., 1., 0.8),
+            DrawTextureParams {
+                dest_size: Some(vec2(texture.width(), texture.height()) / 2.),
+                ..Default::default()
+            },
+        );
+
+        let Some(secondary) = &data.secondary_weapon else {
+            return;
+        };
+        let Some(texture) = data.graphics.textures.get(secondary.hud_icon()) else {
+            return;
+        };
+        draw_texture_ex(
+            texture,
+            16. + texture.width() / 2. + 2.,
+            17. + texture.height() / 8.,
+            Color::new(1., 1., 1., 0.4),
+            DrawTextureParams {
+                dest_size: Some(vec2(texture.width(), texture.height()) / 3.),
+                ..Default::default()
+            },
+        );
+    }
+}
+
 pub struct AberrationMeter {
     sprite: IndexedSprite,
 }
@@ -1,7 +1,9 @@
 use macroquad::{
-    color::WHITE,
+    color::{Color, WHITE},
     math::{Rect, RectOffset},
+    text::{draw_text_ex, get_text_center, TextParams},
     texture::{draw_texture, Texture2D},
+    time::get_time,
 };
 
 use crate::{entity::entities::Ecs, game_data::GameData};
@@ -21,7 +23,18 @@ impl HudMirituhg {
         }
     }
 
-    pub fn draw(&self, _data: &GameData, ecs: &Ecs) {
+    pub fn draw(&self, data: &GameData, ecs: &Ecs) {
+        if let Some(warning) = &data.boss_warning {
+            let pulse = ((get_time() * 8.).sin() * 0.5 + 0.5) as f32;
+            let alpha = (1. - warning.progress()).min(1.) * (0.5 + pulse * 0.5);
+            let text = if data.bosses_defeated > 0 {
+                "The tomb stirs again..."
+            } else {
+                "The tomb awakens..."
+            };
+            draw_centered_text(data, text, 120., 16, Color::new(0.8, 0.1, 0.15, alpha));
+        }
+
         let mirituhgs = ecs.check_components(|e, comps| {
             comps.mirituhg.contains_key(e) && comps.health.contains_key(e)
         });
@@ -32,11 +45,44 @@ impl HudMirituhg {
 
             draw_texture(&self.hud_texture, 0., 0., WHITE);
 
+            let intro_progress = 1. - mirituhg.intro_timer.progress();
             nine_slice::nice_slice(
                 &self.health_bar_texture,
                 &RectOffset::new(3., 2., 2., 2.),
-                &Rect::new(65., 217., (health.hp / mirituhg.max_hp) * 222., 6.),
+                &Rect::new(
+                    65.,
+                    217.,
+                    (health.hp / mirituhg.max_hp) * 222. * intro_progress,
+                    6.,
+                ),
             );
+
+            if !mirituhg.intro_timer.completed() {
+                let alpha = (intro_progress * 4.).min(1.) * ((1. - intro_progress) * 4.).min(1.);
+                draw_centered_text(data, "MIRITUHG", 90., 32, Color::new(1., 1., 1., alpha));
+                draw_centered_text(
+                    data,
+                    "Warden of the Tombs",
+                    110.,
+                    16,
+                    Color::new(0.8, 0.1, 0.15, alpha),
+                );
+            }
         }
     }
 }
+
+fn draw_centered_text(data: &GameData, text: &str, y: f32, font_size: u16, color: Color) {
+    let center = get_text_center(text, Some(&data.ui.font), font_size, 1., 0.);
+    draw_text_ex(
+        text,
+        360. / 2. - center.x,
+        y,
+        TextParams {
+            font: Some(&data.ui.font),
+            font_size,
+            color,
+            ..Default::default()
+        },
+    );
+}
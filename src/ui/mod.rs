@@ -9,6 +9,7 @@ pub mod mirituhg;
 pub mod nine_slice;
 pub mod pause_menu;
 pub mod screen_dimmer;
+pub mod stats_overlay;
 pub mod switcher;
 pub mod ui_data;
 pub mod upgrade_screen;
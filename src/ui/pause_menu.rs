@@ -4,7 +4,11 @@ use macroquad::{
     ui::hash,
 };
 
-use crate::{game_data::GameData, input_manager::Action, settings::WindowSize};
+use crate::{
+    game_data::GameData,
+    input_manager::Action,
+    settings::{AimAssist, FireMode, WindowSize},
+};
 
 use super::{button::button, nine_slice::nice_slice, switcher::switcher};
 
@@ -14,6 +18,8 @@ pub fn pause_menu(data: &mut GameData) -> bool {
     let window_size_id = hash!();
     let music_volume_id = hash!();
     let sfx_volume_id = hash!();
+    let fire_mode_id = hash!();
+    let aim_assist_id = hash!();
     let show_fps_id = hash!();
     let leave_game_id = hash!();
 
@@ -21,6 +27,8 @@ pub fn pause_menu(data: &mut GameData) -> bool {
         window_size_id,
         music_volume_id,
         sfx_volume_id,
+        fire_mode_id,
+        aim_assist_id,
         show_fps_id,
         leave_game_id,
     ];
@@ -113,7 +121,7 @@ pub fn pause_menu(data: &mut GameData) -> bool {
     match switcher(
         data,
         &data.settings,
-        &Rect::new(center.x - switcher_width / 2., 40., switcher_width, 0.),
+        &Rect::new(center.x - switcher_width / 2., 36., switcher_width, 0.),
         "Window Size",
         data.ui.focus.is_some() && window_size_id == data.ui.focus.unwrap(),
         &window_size_text,
@@ -142,7 +150,7 @@ pub fn pause_menu(data: &mut GameData) -> bool {
     match switcher(
         data,
         &data.settings,
-        &Rect::new(center.x - switcher_width / 2., 80., switcher_width, 0.),
+        &Rect::new(center.x - switcher_width / 2., 74., switcher_width, 0.),
         "Music Volume",
         data.ui.focus.is_some() && music_volume_id == data.ui.focus.unwrap(),
         &music_volume_text,
@@ -163,7 +171,7 @@ pub fn pause_menu(data: &mut GameData) -> bool {
     match switcher(
         data,
         &data.settings,
-        &Rect::new(center.x - switcher_width / 2., 120., switcher_width, 0.),
+        &Rect::new(center.x - switcher_width / 2., 112., switcher_width, 0.),
         "SFX Volume",
         data.ui.focus.is_some() && sfx_volume_id == data.ui.focus.unwrap(),
         &sfx_volume_text,
@@ -179,10 +187,78 @@ pub fn pause_menu(data: &mut GameData) -> bool {
         _ => {}
     }
 
+    // Fire Mode
+    let fire_mode_list = FireMode::list();
+    let current_index = fire_mode_list
+        .iter()
+        .position(|m| m == &data.settings.fire_mode)
+        .unwrap();
+    let fire_mode_text = data.settings.fire_mode.text();
+    match switcher(
+        data,
+        &data.settings,
+        &Rect::new(center.x - 95., 150., 90., 0.),
+        "Fire Mode",
+        data.ui.focus.is_some() && fire_mode_id == data.ui.focus.unwrap(),
+        &fire_mode_text,
+    ) {
+        super::switcher::SwitcherAction::Left => {
+            let index = if current_index as i8 - 1 < 0 {
+                fire_mode_list.len() - 1
+            } else {
+                current_index - 1
+            };
+            data.settings.fire_mode = fire_mode_list[index];
+        }
+        super::switcher::SwitcherAction::Right => {
+            let index = if current_index + 1 > fire_mode_list.len() - 1 {
+                0
+            } else {
+                current_index + 1
+            };
+            data.settings.fire_mode = fire_mode_list[index];
+        }
+        _ => {}
+    }
+
+    // Aim Assist
+    let aim_assist_list = AimAssist::list();
+    let current_index = aim_assist_list
+        .iter()
+        .position(|a| a == &data.settings.aim_assist)
+        .unwrap();
+    let aim_assist_text = data.settings.aim_assist.text();
+    match switcher(
+        data,
+        &data.settings,
+        &Rect::new(center.x + 5., 150., 90., 0.),
+        "Aim Assist",
+        data.ui.focus.is_some() && aim_assist_id == data.ui.focus.unwrap(),
+        &aim_assist_text,
+    ) {
+        super::switcher::SwitcherAction::Left => {
+            let index = if current_index as i8 - 1 < 0 {
+                aim_assist_list.len() - 1
+            } else {
+                current_index - 1
+            };
+            data.settings.aim_assist = aim_assist_list[index];
+        }
+        super::switcher::SwitcherAction::Right => {
+            let index = if current_index + 1 > aim_assist_list.len() - 1 {
+                0
+            } else {
+                current_index + 1
+            };
+            data.settings.aim_assist = aim_assist_list[index];
+        }
+        _ => {}
+    }
+
     let button_width = 70.;
     if button(
         data,
-        &Rect::new(center.x - button_width / 2., 165., button_width, 20.),
+        &Rect::new(center.x - 85., 198., button_width, 20.),
         data.ui.focus.is_some() && show_fps_id == data.ui.focus.unwrap(),
         if data.show_fps {
             "Hide FPS"
@@ -202,11 +278,11 @@ pub fn pause_menu(data: &mut GameData) -> bool {
         );
     }
 
-    let button_width = 100.;
+    let button_width = 95.;
     #[cfg(not(target_arch = "wasm32"))]
     if button(
         data,
-        &Rect::new(center.x - button_width / 2., 200., button_width, 20.),
+        &Rect::new(center.x - 10., 198., button_width, 20.),
         data.ui.focus.is_some() && leave_game_id == data.ui.focus.unwrap(),
         "Leave Game",
         None,
@@ -0,0 +1,34 @@
+use macroquad::prelude::*;
+
+use crate::{entity::entities::Ecs, game_data::GameData, stats::Stat};
+
+/// Debug overlay listing how the player's and the weapon's stats are computed.
+pub fn draw_stats_overlay(data: &GameData, ecs: &Ecs) {
+    let sheets = ecs
+        .components
+        .player_data
+        .values()
+        .map(|player_data| ("Player", &player_data.stats))
+        .chain([("Weapon", data.weapon.stat_sheet())]);
+
+    let mut y = 22.;
+    for (name, stats) in sheets {
+        for stat in Stat::ALL {
+            let explanation = stats.explain(stat);
+            if explanation.base == 0. && explanation.modifiers.is_empty() {
+                continue;
+            }
+            draw_text_ex(
+                format!("{} {}", name, explanation).as_str(),
+                2.,
+                y,
+                TextParams {
+                    font: Some(&data.ui.font),
+                    font_size: 16,
+                    ..Default::default()
+                },
+            );
+            y += 10.;
+        }
+    }
+}
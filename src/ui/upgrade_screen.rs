@@ -4,55 +4,97 @@ use crate::{entity::upgrades::Upgrade, game_data::GameData, input_manager::Actio
 
 use super::{button::button, nine_slice::nice_slice};
 
+pub enum UpgradeChoice {
+    Pick(Upgrade),
+    Reroll,
+    /// Index of the offered upgrade to remove from the pool.
+    Banish(usize),
+    Skip,
+}
+
 pub struct UpgradeScreen {
     pub upgrades: Vec<Upgrade>,
-    ids: Vec<u64>,
     pub visible: bool,
+    /// While true the card buttons banish instead of choosing.
+    pub banishing: bool,
 }
 
 impl UpgradeScreen {
     pub fn new(upgrades: Vec<Upgrade>) -> Self {
         Self {
             upgrades,
-            ids: vec![hash!(), hash!(), hash!()],
             visible: false,
+            banishing: false,
         }
     }
 
-    pub fn draw(&self, data: &mut GameData) -> Option<Upgrade> {
-        let mut chosen_upgrade: Option<Upgrade> = None;
+    pub fn draw(&mut self, data: &mut GameData) -> Option<UpgradeChoice> {
+        let mut choice: Option<UpgradeChoice> = None;
+
+        let ids = (0..self.upgrades.len())
+            .map(|i| hash!("upgrade_screen", i))
+            .collect::<Vec<_>>();
+        if ids.is_empty() {
+            return None;
+        }
+        let reroll_id = hash!();
+        let banish_id = hash!();
+        let skip_id = hash!();
+        // The starting weapon has to be picked
+        let show_actions = !self
+            .upgrades
+            .iter()
+            .any(|upgrade| matches!(upgrade, Upgrade::Weapon(_)));
+        let mut rows = vec![ids.clone()];
+        if show_actions {
+            rows.push(vec![reroll_id, banish_id, skip_id]);
+        }
 
-        let ids = &self.ids;
-        if data.ui.focus.is_none() || !ids.contains(&data.ui.focus.unwrap()) {
+        if data.ui.focus.is_none()
+            || !rows
+                .iter()
+                .flatten()
+                .any(|id| *id == data.ui.focus.unwrap())
+        {
             data.ui.focus = Some(ids[0]);
         }
         let focus = data.ui.focus.unwrap();
 
-        let current_index = ids.iter().position(|s| s == &focus).unwrap();
+        let current_row = rows.iter().position(|row| row.contains(&focus)).unwrap();
+        let row = &rows[current_row];
+        let current_index = row.iter().position(|s| s == &focus).unwrap();
         if data.input.is_just_pressed(Action::Left) {
             let index = if current_index as i8 - 1 < 0 {
-                ids.len() - 1
+                row.len() - 1
             } else {
                 current_index - 1
             };
-            data.ui.focus = Some(ids[index]);
+            data.ui.focus = Some(row[index]);
         } else if data.input.is_just_pressed(Action::Right) {
-            let index = if current_index + 1 > ids.len() - 1 {
+            let index = if current_index + 1 > row.len() - 1 {
                 0
             } else {
                 current_index + 1
             };
-            data.ui.focus = Some(ids[index]);
+            data.ui.focus = Some(row[index]);
+        } else if data.input.is_just_pressed(Action::Up) || data.input.is_just_pressed(Action::Down)
+        {
+            let next_row = &rows[(current_row + 1) % rows.len()];
+            data.ui.focus = Some(next_row[current_index.min(next_row.len() - 1)]);
         }
 
-        let container_size = vec2(360. * 0.8, 129. + 26.);
+        let len_f32 = self.upgrades.len() as f32;
         let spacing = 10.;
+        // Widen the container when there are too many frames to fit the upgrade icons
+        let container_size = vec2(
+            (360. * 0.8_f32).max(len_f32 * 80. + (len_f32 - 1.) * spacing),
+            129. + 26.,
+        );
         let container_pos = vec2(
             (360. - container_size.x) / 2.,
             (240. - container_size.y) / 2.,
         );
 
-        let len_f32 = self.upgrades.len() as f32;
         let frame_size = vec2(
             (container_size.x - (len_f32 - 1.) * spacing) / len_f32,
             container_size.y,
@@ -119,15 +161,21 @@ impl UpgradeScreen {
                 },
             );
 
-            if let Upgrade::WeaponUpgrade(_) = upgrade {
+            if let Upgrade::WeaponUpgrade(weapon_upgrade) = upgrade {
+                let evolution = weapon_upgrade.is_evolution();
                 let mut banner_rect = banner_rect.clone();
                 banner_rect.y += 15.;
                 y = banner_rect.y + banner_rect.h;
 
+                // Evolutions stand out with the item banner and list their synergy below
                 let banner_texture = data
                     .graphics
                     .textures
-                    .get("upgrade_banner_upgrade")
+                    .get(if evolution {
+                        "upgrade_banner_item"
+                    } else {
+                        "upgrade_banner_upgrade"
+                    })
                     .unwrap();
                 draw_texture_ex(
                     banner_texture,
@@ -139,7 +187,7 @@ impl UpgradeScreen {
                     },
                 );
 
-                let banner_text = "Upgrade";
+                let banner_text = if evolution { "Evolution" } else { "Upgrade" };
                 let center = get_text_center(banner_text, Some(&data.ui.font), 16, 1., 0.);
                 draw_text_ex(
                     banner_text,
@@ -216,14 +264,72 @@ impl UpgradeScreen {
                     20.,
                 ),
                 is_focused,
-                "Choose",
+                if self.banishing { "Banish" } else { "Choose" },
+                None,
+                Vec2::ZERO,
+            ) {
+                choice = Some(if self.banishing {
+                    UpgradeChoice::Banish(i)
+                } else {
+                    UpgradeChoice::Pick(upgrade.clone())
+                });
+            }
+        }
+
+        if show_actions {
+            let button_size = vec2(80., 20.);
+            let y = container_pos.y + container_size.y + 6.;
+            let x = 180. - (button_size.x * 3. + spacing * 2.) / 2.;
+
+            if button(
+                data,
+                &Rect::new(x, y, button_size.x, button_size.y),
+                focus == reroll_id,
+                format!("Reroll {}", data.upgrades.rerolls).as_str(),
+                None,
+                Vec2::ZERO,
+            ) && data.upgrades.rerolls > 0
+            {
+                choice = Some(UpgradeChoice::Reroll);
+            }
+
+            let banish_text = if self.banishing {
+                "Cancel".to_string()
+            } else {
+                format!("Banish {}", data.upgrades.banishes)
+            };
+            if button(
+                data,
+                &Rect::new(x + button_size.x + spacing, y, button_size.x, button_size.y),
+                focus == banish_id,
+                banish_text.as_str(),
+                None,
+                Vec2::ZERO,
+            ) && (self.banishing || data.upgrades.banishes > 0)
+            {
+                self.banishing = !self.banishing;
+            }
+
+            if button(
+                data,
+                &Rect::new(
+                    x + (button_size.x + spacing) * 2.,
+                    y,
+                    button_size.x,
+                    button_size.y,
+                ),
+                focus == skip_id,
+                "Skip",
                 None,
                 Vec2::ZERO,
             ) {
-                chosen_upgrade = Some(upgrade.clone());
+                choice = Some(UpgradeChoice::Skip);
             }
         }
 
-        chosen_upgrade
+        if choice.is_some() {
+            self.banishing = false;
+        }
+        choice
     }
 }